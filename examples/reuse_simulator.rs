@@ -0,0 +1,29 @@
+use simulate_key::{simulate_key, KeySimulator};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Number of keys sent by each approach
+const KEYS: usize = 100;
+
+fn main() {
+    println!("Sends {} key presses twice, focus a text editor", KEYS);
+    println!("Starting in 3 seconds...");
+    thread::sleep(Duration::from_secs(3));
+
+    // A new backend connection for every key
+    let start = Instant::now();
+    for _ in 0..KEYS {
+        KeySimulator::new().unwrap().press("a").unwrap();
+    }
+    let per_call = start.elapsed();
+
+    // The free functions share one connection for all keys
+    let start = Instant::now();
+    for _ in 0..KEYS {
+        simulate_key("b").unwrap();
+    }
+    let reused = start.elapsed();
+
+    println!("KeySimulator per key: {:?}", per_call);
+    println!("simulate_key:         {:?}", reused);
+}
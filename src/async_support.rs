@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use crate::{parse_combination, split_sequence, KeyCombo, KeySimulator, ParseKeyError, SEPARATORS};
+
+/// Press and hold a key combination like [`simulate_key_hold`](crate::simulate_key_hold)
+/// without blocking the async runtime
+///
+/// The key events are sent synchronously, only the hold duration is awaited
+/// with `tokio::time::sleep`. If the future is dropped while the keys are
+/// held, `Enigo` releases them when the simulator is dropped.
+///
+/// The future is `Send` on Windows and Linux unless the `wayland` feature is
+/// enabled, because the macOS and `wayland` backends cannot move between
+/// threads.
+///
+/// # Examples
+/// ```no_run
+/// # async fn run() {
+/// use simulate_key::simulate_key_hold_async;
+///
+/// simulate_key_hold_async("space", 500).await.unwrap();
+/// # }
+/// ```
+///
+/// # Errors
+/// Returns `ParseKeyError` if the key combination cannot be parsed or a key
+/// event fails
+pub async fn simulate_key_hold_async(
+    key_combination: &str,
+    duration_ms: u64,
+) -> Result<(), ParseKeyError> {
+    let combo = parse_combination(key_combination)?;
+    let mut simulator = KeySimulator::new()?;
+
+    simulator.combo_down(&combo)?;
+    tokio::time::sleep(Duration::from_millis(duration_ms)).await;
+    Ok(simulator.combo_up(&combo)?)
+}
+
+/// Perform comma-separated key combinations like
+/// [`simulate_sequence`](crate::simulate_sequence), awaiting `gap_ms`
+/// milliseconds between them instead of blocking the async runtime
+///
+/// # Examples
+/// ```no_run
+/// # async fn run() {
+/// use simulate_key::simulate_sequence_async;
+///
+/// simulate_sequence_async("ctrl+a, ctrl+c, ctrl+v", 50).await.unwrap();
+/// # }
+/// ```
+///
+/// # Errors
+/// Returns `ParseKeyError` if any segment cannot be parsed or a key event fails
+pub async fn simulate_sequence_async(input: &str, gap_ms: u64) -> Result<(), ParseKeyError> {
+    let combos = split_sequence(input, &SEPARATORS)
+        .into_iter()
+        .map(parse_combination)
+        .collect::<Result<Vec<KeyCombo>, _>>()?;
+    let mut simulator = KeySimulator::new()?;
+
+    for (i, combo) in combos.iter().enumerate() {
+        if i > 0 && gap_ms > 0 {
+            tokio::time::sleep(Duration::from_millis(gap_ms)).await;
+        }
+        simulator.press_combo(combo)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_invalid_input_fails_before_sending() {
+        assert!(matches!(
+            simulate_key_hold_async("ctrl+notakey", 10).await,
+            Err(ParseKeyError::UnknownKey { .. })
+        ));
+        assert!(matches!(
+            simulate_sequence_async("ctrl+a, ctrl+notakey", 10).await,
+            Err(ParseKeyError::UnknownKey { .. })
+        ));
+    }
+
+    // The macOS and `wayland` backends cannot move between threads
+    #[cfg(any(all(target_os = "linux", not(feature = "wayland")), target_os = "windows"))]
+    #[test]
+    fn test_futures_are_send() {
+        fn is_send<T: Send>(_: T) {}
+        is_send(simulate_key_hold_async("space", 10));
+        is_send(simulate_sequence_async("a, b", 10));
+    }
+}
@@ -0,0 +1,191 @@
+use std::ffi::OsString;
+
+use enigo::{Direction, Enigo, InputResult, Key, Keyboard, Settings};
+
+#[cfg(all(target_os = "linux", feature = "uinput"))]
+use crate::uinput::UinputKeyboard;
+
+/// The system interface key events are sent through
+///
+/// On Linux the enabled crate features decide which backends exist. When
+/// several are enabled they are tried in the order of [`Backend::enabled`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Backend {
+    /// The libei remote desktop portal, e.g. on GNOME and KDE
+    Libei,
+    /// The Wayland virtual keyboard protocol, e.g. on wlroots compositors
+    Wayland,
+    /// X11 or XWayland
+    X11,
+    /// A virtual keyboard device through `/dev/uinput`, without a display server
+    Uinput,
+    /// Whatever enigo uses on this platform, e.g. `SendInput` on Windows
+    Native,
+}
+
+/// Display names that never connect, used to keep enigo off a backend
+const NO_X11_DISPLAY: &str = ":-1";
+const NO_WAYLAND_DISPLAY: &str = "simulate-key-disabled";
+
+impl Backend {
+    /// The backends compiled into this build, most preferred first
+    pub(crate) fn enabled() -> Vec<Self> {
+        if !cfg!(target_os = "linux") {
+            return vec![Self::Native];
+        }
+        let mut backends = Vec::new();
+        if cfg!(feature = "libei") {
+            backends.push(Self::Libei);
+        }
+        if cfg!(feature = "wayland") {
+            backends.push(Self::Wayland);
+        }
+        if cfg!(any(feature = "x11rb", feature = "xdo")) {
+            backends.push(Self::X11);
+        }
+        if cfg!(feature = "uinput") {
+            backends.push(Self::Uinput);
+        }
+        backends
+    }
+
+    /// A short name for diagnostics, e.g. "libei" or "xdo"
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Libei => "libei",
+            Self::Wayland => "wayland",
+            // enigo prefers x11rb when both X11 features are enabled
+            Self::X11 if cfg!(feature = "x11rb") => "x11rb",
+            Self::X11 => "xdo",
+            Self::Uinput => "uinput",
+            Self::Native if cfg!(target_os = "windows") => "windows",
+            Self::Native if cfg!(target_os = "macos") => "macos",
+            Self::Native => "native",
+        }
+    }
+
+    /// Why this backend cannot work in the environment described by `var`
+    /// and `settings`, found without trying to connect
+    pub(crate) fn unusable(self, settings: &Settings, var: impl Fn(&str) -> Option<OsString>) -> Option<&'static str> {
+        let is_set = |name: &str| var(name).is_some_and(|value| !value.is_empty());
+        match self {
+            Self::Wayland if settings.wayland_display.is_none() && !is_set("WAYLAND_DISPLAY") => {
+                Some("WAYLAND_DISPLAY is not set")
+            }
+            Self::X11 if settings.x11_display.is_none() && !is_set("DISPLAY") => Some("DISPLAY is not set"),
+            _ => None,
+        }
+    }
+
+    /// `settings` changed so that enigo connects to this backend only
+    ///
+    /// enigo always tries libei when it is compiled in, so only the Wayland
+    /// and X11 connections can be turned off.
+    pub(crate) fn restrict(self, settings: &Settings) -> Settings {
+        let mut settings = settings.clone();
+        if self != Self::Wayland {
+            settings.wayland_display = Some(NO_WAYLAND_DISPLAY.to_string());
+        }
+        if self != Self::X11 {
+            settings.x11_display = Some(NO_X11_DISPLAY.to_string());
+        }
+        settings
+    }
+}
+
+/// An open connection of one of the [`Backend`]s
+pub(crate) enum Device {
+    Enigo(Box<Enigo>),
+    #[cfg(all(target_os = "linux", feature = "uinput"))]
+    Uinput(UinputKeyboard),
+    /// Records the key events instead of sending them, for tests of
+    /// [`KeySimulator`](crate::KeySimulator)
+    #[cfg(test)]
+    Recording(Vec<(Key, Direction)>),
+}
+
+impl Keyboard for Device {
+    fn fast_text(&mut self, text: &str) -> InputResult<Option<()>> {
+        match self {
+            Self::Enigo(enigo) => enigo.fast_text(text),
+            #[cfg(all(target_os = "linux", feature = "uinput"))]
+            Self::Uinput(uinput) => uinput.fast_text(text),
+            #[cfg(test)]
+            Self::Recording(_) => Ok(None),
+        }
+    }
+
+    fn key(&mut self, key: Key, direction: Direction) -> InputResult<()> {
+        match self {
+            Self::Enigo(enigo) => enigo.key(key, direction),
+            #[cfg(all(target_os = "linux", feature = "uinput"))]
+            Self::Uinput(uinput) => uinput.key(key, direction),
+            #[cfg(test)]
+            Self::Recording(events) => {
+                events.push((key, direction));
+                Ok(())
+            }
+        }
+    }
+
+    fn raw(&mut self, keycode: u16, direction: Direction) -> InputResult<()> {
+        match self {
+            Self::Enigo(enigo) => enigo.raw(keycode, direction),
+            #[cfg(all(target_os = "linux", feature = "uinput"))]
+            Self::Uinput(uinput) => uinput.raw(keycode, direction),
+            #[cfg(test)]
+            Self::Recording(_) => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enabled_backends() {
+        let backends = Backend::enabled();
+        assert!(!backends.is_empty());
+        #[cfg(all(target_os = "linux", feature = "xdo", not(feature = "x11rb")))]
+        assert!(backends.iter().any(|b| b.name() == "xdo"));
+        // uinput is the last resort, it works without a display server
+        #[cfg(all(target_os = "linux", feature = "uinput"))]
+        assert_eq!(backends.last(), Some(&Backend::Uinput));
+    }
+
+    #[test]
+    fn test_unusable() {
+        let settings = Settings::default();
+        let x11_only = |name: &str| (name == "DISPLAY").then(|| OsString::from(":0"));
+        assert_eq!(Backend::X11.unusable(&settings, x11_only), None);
+        assert_eq!(
+            Backend::Wayland.unusable(&settings, x11_only),
+            Some("WAYLAND_DISPLAY is not set")
+        );
+        assert_eq!(Backend::X11.unusable(&settings, |_| None), Some("DISPLAY is not set"));
+        assert_eq!(Backend::Libei.unusable(&settings, |_| None), None);
+
+        // An explicit display name in the settings counts as well
+        let settings = Settings {
+            wayland_display: Some("wayland-1".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(Backend::Wayland.unusable(&settings, |_| None), None);
+    }
+
+    #[test]
+    fn test_restrict() {
+        let settings = Settings {
+            x11_display: Some(":1".to_string()),
+            ..Default::default()
+        };
+        let x11 = Backend::X11.restrict(&settings);
+        assert_eq!(x11.x11_display.as_deref(), Some(":1"));
+        assert_eq!(x11.wayland_display.as_deref(), Some(NO_WAYLAND_DISPLAY));
+
+        let libei = Backend::Libei.restrict(&settings);
+        assert_eq!(libei.x11_display.as_deref(), Some(NO_X11_DISPLAY));
+        assert_eq!(libei.wayland_display.as_deref(), Some(NO_WAYLAND_DISPLAY));
+    }
+}
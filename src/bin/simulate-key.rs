@@ -0,0 +1,143 @@
+//! Send key combinations from the shell
+//!
+//! ```text
+//! simulate-key "ctrl+alt+t"
+//! simulate-key "ctrl+a ctrl+c" "wait:200" "ctrl+v"
+//! echo "text hello" | simulate-key --macro
+//! ```
+
+use std::io::{self, IsTerminal, Read, Write};
+use std::process::ExitCode;
+use std::thread;
+use std::time::Duration;
+
+use simulate_key::{get_supported_keys, parse_key_sequence, parse_macro, KeySimulator, ParseKeyError};
+
+const USAGE: &str = "\
+Usage: simulate-key [OPTIONS] [INPUT]...
+
+Sends every INPUT in order. Without INPUT, the lines of stdin are read.
+
+By default an INPUT is a key sequence: combinations separated by spaces or
+commas, with pauses written as wait:<ms>, e.g. \"ctrl+c wait:200 ctrl+v\".
+
+Options:
+  -m, --macro       Run the input as a macro script (combos, hold, text, wait)
+  -t, --text        Type the input as text
+  -d, --delay <MS>  Wait before sending the first key, e.g. to focus a window
+  -l, --list-keys   Print the supported key names
+  -h, --help        Print this help
+  -V, --version     Print the version
+
+Exit status: 0 on success, 1 if the input is invalid or a key cannot be sent,
+2 on invalid arguments.";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Sequence,
+    Macro,
+    Text,
+}
+
+fn main() -> ExitCode {
+    let mut mode = Mode::Sequence;
+    let mut delay = Duration::ZERO;
+    let mut inputs = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-m" | "--macro" => mode = Mode::Macro,
+            "-t" | "--text" => mode = Mode::Text,
+            "-d" | "--delay" => match args.next().map(|ms| ms.parse()) {
+                Some(Ok(ms)) => delay = Duration::from_millis(ms),
+                _ => return usage_error("--delay needs a number of milliseconds"),
+            },
+            "-l" | "--list-keys" => {
+                // Stop quietly when the reader goes away, e.g. `| head`
+                let mut stdout = io::stdout().lock();
+                let _ = get_supported_keys().iter().try_for_each(|key| writeln!(stdout, "{}", key));
+                return ExitCode::SUCCESS;
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS;
+            }
+            "-V" | "--version" => {
+                println!("simulate-key {}", env!("CARGO_PKG_VERSION"));
+                return ExitCode::SUCCESS;
+            }
+            "--" => {
+                inputs.extend(args.by_ref());
+            }
+            option if is_option(option) => {
+                return usage_error(&format!("unknown option '{}'", option));
+            }
+            _ => inputs.push(arg),
+        }
+    }
+
+    let input = if inputs.is_empty() {
+        if io::stdin().is_terminal() {
+            return usage_error("no input given");
+        }
+        let mut input = String::new();
+        if let Err(e) = io::stdin().read_to_string(&mut input) {
+            eprintln!("simulate-key: failed to read stdin: {}", e);
+            return ExitCode::FAILURE;
+        }
+        input
+    } else {
+        let separator = if mode == Mode::Text { " " } else { "\n" };
+        inputs.join(separator)
+    };
+
+    match run(mode, &input, delay) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("simulate-key: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Validate the whole input, then send it
+fn run(mode: Mode, input: &str, delay: Duration) -> Result<(), ParseKeyError> {
+    let lines: Vec<&str> = input.lines().filter(|line| !line.trim().is_empty()).collect();
+    match mode {
+        Mode::Sequence => {
+            for line in &lines {
+                parse_key_sequence(line)?;
+            }
+        }
+        Mode::Macro => {
+            parse_macro(input)?;
+        }
+        Mode::Text => {}
+    }
+
+    let mut simulator = KeySimulator::new()?;
+    thread::sleep(delay);
+    match mode {
+        Mode::Sequence => {
+            for line in lines {
+                simulator.press_key_sequence(line, 0)?;
+            }
+        }
+        Mode::Macro => simulator.run_macro(input)?,
+        Mode::Text => simulator.text(input.strip_suffix('\n').unwrap_or(input))?,
+    }
+    Ok(())
+}
+
+/// Whether `arg` looks like an option rather than a key, e.g. "--macro"
+/// but not "-" (the minus key)
+fn is_option(arg: &str) -> bool {
+    arg.strip_prefix('-')
+        .is_some_and(|rest| rest.starts_with(|c: char| c == '-' || c.is_ascii_alphabetic()))
+}
+
+fn usage_error(message: &str) -> ExitCode {
+    eprintln!("simulate-key: {}\n\n{}", message, USAGE);
+    ExitCode::from(2)
+}
@@ -0,0 +1,387 @@
+use std::fmt;
+use std::str::FromStr;
+
+use enigo::Key;
+
+use crate::{
+    key_name, modifier_rank, offset_in, parse_combination, parse_combination_with, split_combination,
+    KeySimulator, ParseKeyError, ParseOptions, SimulateError,
+};
+
+/// A parsed key combination: the modifiers to hold and the keys to click
+///
+/// Usually there is a single main key, but combinations like "alt+tab+tab"
+/// click several keys in order while the modifiers stay held.
+///
+/// Parse once with [`str::parse`] and reuse the value, e.g. in config
+/// structs. Modifiers are kept in the fixed order ctrl, alt, shift, meta, so
+/// "shift+ctrl+t" and "ctrl+shift+t" are equal and hash the same, which makes
+/// `KeyCombo` usable as a `HashMap` key for hotkey tables. `Display` prints a canonical
+/// string with lowercase key names that parses back to the same combination.
+///
+/// # Examples
+/// ```
+/// use simulate_key::{KeyCombo, Key};
+///
+/// let combo: KeyCombo = "Control + Shift + Return".parse().unwrap();
+/// assert_eq!(combo.modifiers(), &[Key::Control, Key::Shift]);
+/// assert_eq!(combo.key(), Key::Return);
+/// assert_eq!(combo.to_string(), "ctrl+shift+enter");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeyCombo {
+    modifiers: Vec<Key>,
+    keys: Vec<Key>,
+}
+
+impl KeyCombo {
+    /// Create a combination with a single main key from already resolved keys
+    pub fn new(modifiers: Vec<Key>, key: Key) -> Self {
+        Self::with_keys(modifiers, vec![key])
+    }
+
+    /// Create a combination that clicks several keys in order
+    ///
+    /// # Panics
+    /// Panics if `keys` is empty
+    pub fn with_keys(modifiers: Vec<Key>, keys: Vec<Key>) -> Self {
+        assert!(!keys.is_empty(), "a key combination needs at least one key");
+        let mut modifiers = modifiers;
+        modifiers.sort_by_key(|modifier| modifier_rank(*modifier));
+        Self { modifiers, keys }
+    }
+
+    /// The modifier keys, in the order they are pressed
+    pub fn modifiers(&self) -> &[Key] {
+        &self.modifiers
+    }
+
+    /// The main key, the last one if several keys are clicked
+    pub fn key(&self) -> Key {
+        self.keys[self.keys.len() - 1]
+    }
+
+    /// All keys that are clicked while the modifiers are held, in order
+    pub fn keys(&self) -> &[Key] {
+        &self.keys
+    }
+
+    /// Perform the combination with a new [`KeySimulator`]
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use simulate_key::KeyCombo;
+    ///
+    /// let combo: KeyCombo = "ctrl+shift+t".parse().unwrap();
+    /// combo.send().unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// Returns `SimulateError` if the `Enigo` instance cannot be created or a
+    /// key event fails
+    pub fn send(&self) -> Result<(), SimulateError> {
+        self.send_with(&mut KeySimulator::new()?)
+    }
+
+    /// Perform the combination with an existing [`KeySimulator`]
+    ///
+    /// # Errors
+    /// Returns `SimulateError` if a key event fails
+    pub fn send_with(&self, simulator: &mut KeySimulator) -> Result<(), SimulateError> {
+        simulator.press_combo(self)
+    }
+}
+
+/// Another name for [`KeyCombo`]
+pub type KeyCombination = KeyCombo;
+
+impl FromStr for KeyCombo {
+    type Err = ParseKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_combination(s)
+    }
+}
+
+impl TryFrom<&str> for KeyCombo {
+    type Error = ParseKeyError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl TryFrom<&String> for KeyCombo {
+    type Error = ParseKeyError;
+
+    fn try_from(s: &String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl TryFrom<String> for KeyCombo {
+    type Error = ParseKeyError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for KeyCombo {
+    /// Named keys are printed in lowercase and single characters keep their
+    /// case. Keys without a name, e.g. `Key::Other`, are printed in their
+    /// `Debug` form.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for modifier in &self.modifiers {
+            write!(f, "{}+", DisplayKey(*modifier))?;
+        }
+        for (i, key) in self.keys.iter().enumerate() {
+            if i > 0 {
+                f.write_str("+")?;
+            }
+            write!(f, "{}", DisplayKey(*key))?;
+        }
+        Ok(())
+    }
+}
+
+/// Prints the canonical name of a single key
+struct DisplayKey(Key);
+
+impl fmt::Display for DisplayKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match key_name(self.0) {
+            Some(name) => f.write_str(&name),
+            None => write!(f, "{:?}", self.0),
+        }
+    }
+}
+
+/// Parse whitespace-separated chords like "ctrl+k ctrl+s" into their combinations
+///
+/// Every chord is validated before anything is returned, so a typo in the
+/// second chord is caught before the first one is sent. Spaces around a
+/// separator stay part of the chord, so "ctrl + k ctrl + s" is two chords as
+/// well. Use the `plus` and `minus` names for a lone separator key.
+///
+/// # Errors
+/// Returns the `ParseKeyError` of the first chord that cannot be parsed
+pub fn parse_chord_sequence(input: &str) -> Result<Vec<KeyCombo>, ParseKeyError> {
+    parse_chord_sequence_with(input, &ParseOptions::default())
+}
+
+/// Parse whitespace-separated chords with custom parse options
+pub(crate) fn parse_chord_sequence_with(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<Vec<KeyCombo>, ParseKeyError> {
+    let chords = split_chords(input, options.separators());
+    if chords.is_empty() {
+        return Err(ParseKeyError::EmptyCombination);
+    }
+
+    chords
+        .into_iter()
+        .map(|chord| {
+            parse_combination_with(chord, options).map_err(|e| e.offset(offset_in(input, chord)))
+        })
+        .collect()
+}
+
+/// Split input on the whitespace between chords
+///
+/// Whitespace only separates two chords if the text before it is a complete
+/// combination and the text after it does not start with a separator.
+pub(crate) fn split_chords<'a>(input: &'a str, separators: &[char]) -> Vec<&'a str> {
+    let mut chords = Vec::new();
+    let mut chord: Option<(usize, usize)> = None;
+
+    for (start, end) in tokens(input) {
+        chord = match chord {
+            Some((chord_start, chord_end))
+                if !ends_with_separator(&input[chord_start..chord_end], separators)
+                    && !input[start..end].starts_with(separators) =>
+            {
+                chords.push(&input[chord_start..chord_end]);
+                Some((start, end))
+            }
+            Some((chord_start, _)) => Some((chord_start, end)),
+            None => Some((start, end)),
+        };
+    }
+    if let Some((chord_start, chord_end)) = chord {
+        chords.push(&input[chord_start..chord_end]);
+    }
+
+    chords
+}
+
+/// Byte ranges of the whitespace-separated tokens in `input`
+fn tokens(input: &str) -> Vec<(usize, usize)> {
+    let mut tokens = Vec::new();
+    let mut token_start = None;
+
+    for (i, c) in input.char_indices() {
+        match (c.is_whitespace(), token_start) {
+            (true, Some(start)) => {
+                tokens.push((start, i));
+                token_start = None;
+            }
+            (false, None) => token_start = Some(i),
+            _ => {}
+        }
+    }
+    if let Some(start) = token_start {
+        tokens.push((start, input.len()));
+    }
+
+    tokens
+}
+
+/// Check if a combination still expects a key after its last separator
+fn ends_with_separator(chord: &str, separators: &[char]) -> bool {
+    split_combination(chord, separators)
+        .last()
+        .is_some_and(|part| part.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SEPARATORS;
+
+    #[test]
+    fn test_from_str_and_display() {
+        let combo: KeyCombo = "ctrl+shift+t".parse().unwrap();
+        assert_eq!(combo, KeyCombo::new(vec![Key::Control, Key::Shift], Key::Unicode('t')));
+        assert_eq!(combo.to_string(), "ctrl+shift+t");
+
+        for (input, canonical) in [
+            ("Control + Esc", "ctrl+escape"),
+            ("cmd+pgdn", "meta+pagedown"),
+            ("ctrl++", "ctrl+plus"),
+            ("ctrl--", "ctrl+minus"),
+            ("alt+,", "alt+,"),
+            ("win", "meta"),
+            ("shift+A", "shift+A"),
+            ("Alt + Tab + Tab", "alt+tab+tab"),
+        ] {
+            let combo: KeyCombo = input.parse().unwrap();
+            assert_eq!(combo.to_string(), canonical);
+            assert_eq!(canonical.parse::<KeyCombo>(), Ok(combo));
+        }
+
+        assert!("ctrl+notakey".parse::<KeyCombo>().is_err());
+        assert!(KeyCombo::try_from("ctrl+c").is_ok());
+        assert!(KeyCombo::try_from(String::from("ctrl+c")).is_ok());
+    }
+
+    #[test]
+    fn test_canonical_modifier_order() {
+        let combo: KeyCombo = "SHIFT + CONTROL + t".parse().unwrap();
+        assert_eq!(combo.to_string(), "ctrl+shift+t");
+        assert_eq!(combo, "ctrl+shift+t".parse().unwrap());
+
+        let combo: KeyCombo = "cmd+shift+alt+control+x".parse().unwrap();
+        assert_eq!(combo.modifiers(), &[Key::Control, Key::Alt, Key::Shift, Key::Meta]);
+        assert_eq!(combo.to_string(), "ctrl+alt+shift+meta+x");
+    }
+
+    #[test]
+    fn test_equality_ignores_modifier_order_and_aliases() {
+        use std::collections::HashMap;
+
+        let modifiers = [
+            ["ctrl", "control"],
+            ["alt", "alt"],
+            ["shift", "shift"],
+            ["meta", "win"],
+            ["meta", "cmd"],
+            ["meta", "command"],
+            ["lctrl", "lcontrol"],
+            ["rctrl", "rcontrol"],
+        ];
+        for [a, b] in modifiers {
+            for [c, d] in modifiers {
+                let first: KeyCombo = format!("{}+{}+c", a, c).parse().unwrap();
+                let second: KeyCombo = format!("{}+{}+c", d, b).parse().unwrap();
+                assert_eq!(first, second, "{}+{} vs {}+{}", a, c, d, b);
+            }
+        }
+
+        let mut bindings = HashMap::new();
+        bindings.insert("shift+ctrl+c".parse::<KeyCombo>().unwrap(), "copy");
+        // Single characters keep their case, 'C' is a different key than 'c'
+        assert_eq!(bindings.get(&"Control + Shift + C".parse().unwrap()), None);
+        assert_eq!(bindings.get(&"control+shift+c".parse().unwrap()), Some(&"copy"));
+        assert_eq!("lctrl+ctrl+c".parse::<KeyCombo>(), "ctrl+lctrl+c".parse());
+        assert_ne!("ctrl+c".parse::<KeyCombo>(), "lctrl+c".parse());
+    }
+
+    #[test]
+    fn test_display_aliases() {
+        for (alias, canonical) in [
+            ("control+a", "ctrl+a"),
+            ("win+a", "meta+a"),
+            ("cmd+a", "meta+a"),
+            ("command+a", "meta+a"),
+            ("lcontrol+a", "lctrl+a"),
+            ("rcontrol+a", "rctrl+a"),
+            ("return", "enter"),
+            ("del", "delete"),
+            ("ins", "insert"),
+            ("esc", "escape"),
+            ("pgup", "pageup"),
+            ("pgdn", "pagedown"),
+            ("leftarrow", "left"),
+            ("rightarrow", "right"),
+            ("uparrow", "up"),
+            ("downarrow", "down"),
+            ("caps", "capslock"),
+            ("num", "numlock"),
+            ("scroll", "scrolllock"),
+            ("prtsc", "printscreen"),
+            ("volup", "volumeup"),
+            ("voldown", "volumedown"),
+            ("mute", "volumemute"),
+            ("play", "mediaplay"),
+            ("stop", "mediastop"),
+            ("next", "medianext"),
+            ("prev", "mediaprev"),
+            ("F5", "f5"),
+        ] {
+            let combo: KeyCombo = alias.parse().unwrap();
+            assert_eq!(combo.to_string(), canonical, "{}", alias);
+        }
+
+        #[cfg(target_os = "windows")]
+        for (alias, canonical) in [("lwin+a", "lmeta+a"), ("rwin+a", "rmeta+a"), ("lcmd+a", "lmeta+a")] {
+            let combo: KeyCombo = alias.parse().unwrap();
+            assert_eq!(combo.to_string(), canonical, "{}", alias);
+        }
+    }
+
+    #[test]
+    fn test_split_chords() {
+        assert_eq!(split_chords("ctrl+k ctrl+s", &SEPARATORS), vec!["ctrl+k", "ctrl+s"]);
+        assert_eq!(split_chords("  ctrl+k   ctrl+s  ", &SEPARATORS), vec!["ctrl+k", "ctrl+s"]);
+        assert_eq!(split_chords("Ctrl + K Ctrl - S", &SEPARATORS), vec!["Ctrl + K", "Ctrl - S"]);
+        assert_eq!(split_chords("ctrl+- a", &SEPARATORS), vec!["ctrl+-", "a"]);
+        assert_eq!(split_chords("ctrl++ a", &SEPARATORS), vec!["ctrl++", "a"]);
+        assert_eq!(split_chords("enter", &SEPARATORS), vec!["enter"]);
+        assert!(split_chords("   ", &SEPARATORS).is_empty());
+    }
+
+    #[test]
+    fn test_parse_chord_sequence() {
+        assert_eq!(
+            parse_chord_sequence("ctrl+k ctrl+s"),
+            Ok(vec![
+                KeyCombo::new(vec![Key::Control], Key::Unicode('k')),
+                KeyCombo::new(vec![Key::Control], Key::Unicode('s')),
+            ])
+        );
+        assert!(parse_chord_sequence("ctrl+k ctrl+notakey").is_err());
+        assert!(parse_chord_sequence("").is_err());
+    }
+}
@@ -0,0 +1,110 @@
+use crate::SimulateError;
+
+/// Check that the foreground window would receive simulated keys
+///
+/// On Windows, User Interface Privilege Isolation (UIPI) silently drops
+/// input sent to a window of a process with a higher integrity level, e.g.
+/// an application running as administrator while this process is not. This
+/// returns `SimulateError::TargetElevated` in that case, so the user can be
+/// told to run elevated as well. [`KeySimulator`](crate::KeySimulator) checks
+/// this before sending anything. Other platforms always return `Ok`.
+///
+/// # Examples
+/// ```no_run
+/// if let Err(e) = simulate_key::check_target_elevation() {
+///     eprintln!("{}", e);
+/// }
+/// ```
+///
+/// # Errors
+/// Returns `SimulateError::TargetElevated` if the foreground window belongs
+/// to a process with a higher integrity level. If the integrity levels
+/// cannot be determined, keys are sent as usual.
+pub fn check_target_elevation() -> Result<(), SimulateError> {
+    #[cfg(windows)]
+    if windows_impl::foreground_is_elevated() {
+        return Err(SimulateError::TargetElevated);
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+mod windows_impl {
+    use std::ffi::c_void;
+
+    use windows::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows::Win32::Security::{
+        GetSidSubAuthority, GetSidSubAuthorityCount, GetTokenInformation, TokenIntegrityLevel,
+        TOKEN_MANDATORY_LABEL, TOKEN_QUERY,
+    };
+    use windows::Win32::System::Threading::{
+        GetCurrentProcess, OpenProcess, OpenProcessToken, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+
+    /// Whether the foreground window runs at a higher integrity level than
+    /// this process, `false` if either level is unknown
+    pub(super) fn foreground_is_elevated() -> bool {
+        let own = unsafe { integrity_level(GetCurrentProcess()) };
+        match (own, foreground_integrity_level()) {
+            (Some(own), Some(target)) => target > own,
+            _ => false,
+        }
+    }
+
+    fn foreground_integrity_level() -> Option<u32> {
+        let window = unsafe { GetForegroundWindow() };
+        if window.is_invalid() {
+            return None;
+        }
+        let mut pid = 0;
+        unsafe { GetWindowThreadProcessId(window, Some(&mut pid)) };
+        if pid == 0 {
+            return None;
+        }
+
+        let process = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) }.ok()?;
+        let level = unsafe { integrity_level(process) };
+        let _ = unsafe { CloseHandle(process) };
+        level
+    }
+
+    /// The mandatory integrity level RID of a process, e.g. 0x2000 for medium
+    /// and 0x3000 for high
+    unsafe fn integrity_level(process: HANDLE) -> Option<u32> {
+        let mut token = HANDLE::default();
+        unsafe { OpenProcessToken(process, TOKEN_QUERY, &mut token) }.ok()?;
+
+        let mut len = 0;
+        // The first call only reports the size of the label
+        let _ = unsafe { GetTokenInformation(token, TokenIntegrityLevel, None, 0, &mut len) };
+        let mut buffer = vec![0u64; (len as usize).div_ceil(8)];
+        let level = unsafe {
+            GetTokenInformation(
+                token,
+                TokenIntegrityLevel,
+                Some(buffer.as_mut_ptr() as *mut c_void),
+                len,
+                &mut len,
+            )
+        }
+        .ok()
+        .map(|()| unsafe {
+            let label = &*(buffer.as_ptr() as *const TOKEN_MANDATORY_LABEL);
+            let count = *GetSidSubAuthorityCount(label.Label.Sid);
+            *GetSidSubAuthority(label.Label.Sid, u32::from(count) - 1)
+        });
+
+        let _ = unsafe { CloseHandle(token) };
+        level
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(windows))]
+    #[test]
+    fn test_no_elevation_check_off_windows() {
+        assert_eq!(super::check_target_elevation(), Ok(()));
+    }
+}
@@ -0,0 +1,471 @@
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+use std::time::Duration;
+
+use enigo::{InputError, NewConError};
+
+use crate::{MAX_REPEAT, MAX_WAIT_MS};
+
+/// Everything that can go wrong while parsing or simulating keys
+///
+/// Functions that take key names as strings return this error. Every variant
+/// except [`Simulate`](ParseKeyError::Simulate) is a problem with the input
+/// and fails again the same way when retried. Functions that only send
+/// already parsed keys, like [`KeyCombo::send`](crate::KeyCombo::send),
+/// return [`SimulateError`] instead.
+///
+/// Parse failures carry the offending text and, where it is a single part of
+/// the input, its byte range as returned by [`span`](ParseKeyError::span), so
+/// callers can react to them
+/// without matching on the error message, e.g. only suggest a key name for
+/// [`UnknownKey`](ParseKeyError::UnknownKey). New variants may be added in
+/// minor releases, so matches need a wildcard arm.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ParseKeyError {
+    /// The input was empty or only whitespace
+    EmptyCombination,
+    /// A key is missing between two separators, e.g. "ctrl++c"
+    EmptySegment {
+        /// 1-based position of the empty segment
+        position: usize,
+        combination: String,
+        span: Range<usize>,
+    },
+    /// The combination ends with a separator, e.g. "ctrl+"
+    MissingKey {
+        /// The last key before the trailing separator
+        after: String,
+        combination: String,
+        /// The trailing separator
+        span: Range<usize>,
+    },
+    /// A name in front of the main key is not a known modifier or key
+    UnknownModifier {
+        name: String,
+        /// A supported key name that is close to `name`, if there is one
+        suggestion: Option<String>,
+        span: Range<usize>,
+    },
+    /// A regular key stands where only modifiers are allowed, e.g. "a+b"
+    /// when [`ParseOptions::multiple_keys`](crate::ParseOptions::multiple_keys)
+    /// is disabled
+    NotAModifier {
+        name: String,
+        combination: String,
+        span: Range<usize>,
+    },
+    /// A key name is not known
+    UnknownKey {
+        name: String,
+        /// A supported key name that is close to `name`, if there is one
+        suggestion: Option<String>,
+        span: Range<usize>,
+    },
+    /// A key name is known but the key does not exist on this platform, e.g.
+    /// "wake" on Windows
+    UnsupportedKey { name: String, span: Range<usize> },
+    /// A key name is an alias or not lowercase and aliases are rejected
+    NonCanonicalName {
+        name: String,
+        canonical: String,
+        span: Range<usize>,
+    },
+    /// Only modifiers are clicked and modifier-only combinations are rejected
+    ModifierOnly { combination: String },
+    /// The combination has more segments than allowed
+    TooManySegments { max: usize, combination: String },
+    /// A modifier appears more than once and duplicates are rejected
+    DuplicateModifier {
+        name: String,
+        combination: String,
+        span: Range<usize>,
+    },
+    /// A character is followed by combining marks, e.g. a decomposed "é"
+    CombiningSequence { key: String, span: Range<usize> },
+    /// A `*N` repeat count is out of range
+    InvalidRepeat {
+        part: String,
+        combination: String,
+        span: Range<usize>,
+    },
+    /// A `wait:` token in a key sequence has an invalid duration
+    InvalidWait { token: String, span: Range<usize> },
+    /// A custom alias name is empty or contains a separator or whitespace, see
+    /// [`ParseOptions::add_alias`](crate::ParseOptions::add_alias)
+    InvalidAlias { name: String },
+    /// A list of keys held together was empty
+    EmptyChord,
+    /// A key sequence had no entries
+    EmptySequence,
+    /// An entry of a key sequence could not be parsed
+    InvalidEntry {
+        /// 1-based position of the entry
+        position: usize,
+        entry: String,
+        error: Box<ParseKeyError>,
+    },
+    /// A line of a macro script could not be parsed
+    InvalidLine {
+        /// 1-based line number
+        line: usize,
+        text: String,
+        error: Box<ParseKeyError>,
+    },
+    /// A step of a [`Macro`](crate::Macro) could not be parsed or sent
+    StepFailed {
+        /// 0-based index of the step, in the order the steps were added
+        index: usize,
+        error: Box<ParseKeyError>,
+    },
+    /// The input was valid but sending it failed
+    Simulate(SimulateError),
+}
+
+/// Message of enigo when a Wayland compositor grants no input protocol
+const WAYLAND_NO_PROTOCOL: &str = "no protocol available to simulate input";
+
+/// Failures of the input backend while sending already parsed keys
+///
+/// These depend on the state of the system rather than on the input, so
+/// every variant may succeed when retried, e.g. once a display server is up
+/// or a permission was granted.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum SimulateError {
+    /// The `Enigo` instance could not be created
+    Connection(NewConError),
+    /// The `Enigo` instance could not be created because there is no display
+    /// server to send keys to, e.g. in CI on Linux with neither `DISPLAY` nor
+    /// `WAYLAND_DISPLAY` set. With several Linux backends enabled,
+    /// [`NoBackend`](Self::NoBackend) is returned instead.
+    NoDisplayServer(NewConError),
+    /// The application is not allowed to simulate input, e.g. on macOS
+    /// without the Accessibility permission, see
+    /// [`check_permissions`](crate::check_permissions)
+    PermissionDenied,
+    /// The foreground window belongs to a process with a higher integrity
+    /// level, e.g. one running as administrator, and Windows would silently
+    /// drop the keys, see [`check_target_elevation`](crate::check_target_elevation)
+    TargetElevated,
+    /// The input backend did not come up within the timeout, see
+    /// [`KeySimulator::with_timeout`](crate::KeySimulator::with_timeout)
+    BackendTimeout(Duration),
+    /// The user still held a modifier on the physical keyboard when the
+    /// timeout ran out, see [`wait_for_release`](crate::wait_for_release)
+    ReleaseTimeout(Duration),
+    /// Several Linux backends are enabled and none of them could be created,
+    /// with the name of every backend tried and why it failed
+    NoBackend(Vec<(&'static str, SimulateError)>),
+    /// The `uinput` backend could not open `/dev/uinput`, e.g. because this
+    /// user may not write to it
+    Uinput(std::io::ErrorKind),
+    /// The system rejected a key event
+    Backend(InputError),
+}
+
+impl ParseKeyError {
+    /// The byte range of the input that caused the error, e.g. `6..10` for
+    /// the unknown modifier in "ctrl+ shif +c"
+    ///
+    /// For sequences and macro scripts the range refers to the whole input.
+    /// Errors that are not about a single part of the input, e.g. backend
+    /// failures, have no span.
+    pub fn span(&self) -> Option<Range<usize>> {
+        match self {
+            Self::EmptySegment { span, .. }
+            | Self::MissingKey { span, .. }
+            | Self::UnknownModifier { span, .. }
+            | Self::UnknownKey { span, .. }
+            | Self::UnsupportedKey { span, .. }
+            | Self::NotAModifier { span, .. }
+            | Self::NonCanonicalName { span, .. }
+            | Self::DuplicateModifier { span, .. }
+            | Self::CombiningSequence { span, .. }
+            | Self::InvalidRepeat { span, .. }
+            | Self::InvalidWait { span, .. } => Some(span.clone()),
+            Self::InvalidEntry { error, .. } | Self::InvalidLine { error, .. } => error.span(),
+            Self::StepFailed { error, .. } => error.span(),
+            _ => None,
+        }
+    }
+
+    /// Whether the input could not be parsed, as opposed to a failure while
+    /// sending it
+    ///
+    /// Parse errors fail the same way every time, so retrying is pointless.
+    pub fn is_parse_error(&self) -> bool {
+        match self {
+            Self::StepFailed { error, .. } => error.is_parse_error(),
+            _ => !matches!(self, Self::Simulate(_)),
+        }
+    }
+
+    /// See [`SimulateError::is_backend_unavailable`]
+    pub fn is_backend_unavailable(&self) -> bool {
+        match self {
+            Self::StepFailed { error, .. } => error.is_backend_unavailable(),
+            _ => matches!(self, Self::Simulate(e) if e.is_backend_unavailable()),
+        }
+    }
+
+    /// See [`SimulateError::is_permission_denied`]
+    pub fn is_permission_denied(&self) -> bool {
+        match self {
+            Self::StepFailed { error, .. } => error.is_permission_denied(),
+            _ => matches!(self, Self::Simulate(e) if e.is_permission_denied()),
+        }
+    }
+
+    /// Move the span by `offset` bytes, for errors found in a part of a larger input
+    pub(crate) fn offset(mut self, offset: usize) -> Self {
+        self.shift_span(offset);
+        self
+    }
+
+    fn shift_span(&mut self, offset: usize) {
+        match self {
+            Self::EmptySegment { span, .. }
+            | Self::MissingKey { span, .. }
+            | Self::UnknownModifier { span, .. }
+            | Self::UnknownKey { span, .. }
+            | Self::UnsupportedKey { span, .. }
+            | Self::NotAModifier { span, .. }
+            | Self::NonCanonicalName { span, .. }
+            | Self::DuplicateModifier { span, .. }
+            | Self::CombiningSequence { span, .. }
+            | Self::InvalidRepeat { span, .. }
+            | Self::InvalidWait { span, .. } => {
+                span.start += offset;
+                span.end += offset;
+            }
+            Self::InvalidEntry { error, .. } | Self::InvalidLine { error, .. } => error.shift_span(offset),
+            _ => {}
+        }
+    }
+
+    /// Write the message without the "ParseKeyError: " prefix
+    fn fmt_message(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyCombination => f.write_str("Empty key combination"),
+            Self::EmptySegment { position, combination, .. } => write!(
+                f,
+                "Empty key segment at position {} in '{}'",
+                position, combination
+            ),
+            Self::MissingKey { after, combination, .. } => write!(
+                f,
+                "Combination '{}' ends with a separator, no main key after '{}'",
+                combination, after
+            ),
+            Self::UnknownModifier { name, suggestion, .. } => {
+                write!(f, "Unknown modifier: {:?}", name)?;
+                fmt_suggestion(f, suggestion)
+            }
+            Self::NotAModifier { name, combination, .. } => write!(
+                f,
+                "'{}' is not a modifier; only the last key in '{}' may be a regular key",
+                name, combination
+            ),
+            Self::UnknownKey { name, suggestion, .. } => {
+                write!(f, "Unknown key: {:?}", name)?;
+                fmt_suggestion(f, suggestion)
+            }
+            Self::UnsupportedKey { name, .. } => {
+                write!(f, "Key {:?} is not supported on this platform", name)
+            }
+            Self::NonCanonicalName { name, canonical, .. } => {
+                write!(f, "Non-canonical key name: {} (use '{}')", name, canonical)
+            }
+            Self::ModifierOnly { combination } => {
+                write!(f, "No key besides modifiers in '{}'", combination)
+            }
+            Self::TooManySegments { max, combination } => {
+                write!(f, "More than {} keys in '{}'", max, combination)
+            }
+            Self::DuplicateModifier { name, combination, .. } => {
+                write!(f, "Duplicate modifier: {} in '{}'", name, combination)
+            }
+            Self::CombiningSequence { key, .. } => write!(
+                f,
+                "Key '{}' is a character followed by combining marks; use the precomposed character or type_text",
+                key
+            ),
+            Self::InvalidRepeat { part, combination, .. } => write!(
+                f,
+                "Repeat count must be between 1 and {}: {} in '{}'",
+                MAX_REPEAT, part, combination
+            ),
+            Self::InvalidWait { token, .. } => write!(
+                f,
+                "Invalid wait duration: '{}' (expected 0 to {} milliseconds)",
+                token, MAX_WAIT_MS
+            ),
+            Self::InvalidAlias { name } => write!(
+                f,
+                "Invalid alias name: {:?} (expected a single word without '+' or '-')",
+                name
+            ),
+            Self::EmptyChord => f.write_str("Empty key chord"),
+            Self::EmptySequence => f.write_str("Empty key sequence"),
+            Self::InvalidEntry { position, entry, error } => {
+                write!(f, "Entry {} '{}': ", position, entry)?;
+                error.fmt_message(f)
+            }
+            Self::InvalidLine { line, text, error } => {
+                write!(f, "Line {} '{}': ", line, text)?;
+                error.fmt_message(f)
+            }
+            Self::StepFailed { index, error } => {
+                write!(f, "Step {}: ", index)?;
+                error.fmt_message(f)
+            }
+            Self::Simulate(e) => e.fmt_message(f),
+        }
+    }
+}
+
+impl SimulateError {
+    /// Whether no input backend could be set up at all, e.g. because there is
+    /// no display server or the permission to simulate input is missing
+    pub fn is_backend_unavailable(&self) -> bool {
+        matches!(
+            self,
+            Self::Connection(_)
+                | Self::NoDisplayServer(_)
+                | Self::PermissionDenied
+                | Self::BackendTimeout(_)
+                | Self::NoBackend(_)
+                | Self::Uinput(_)
+        )
+    }
+
+    /// Whether the system refused to let this process simulate input
+    ///
+    /// This is the case when the macOS accessibility permission is missing and
+    /// when a Wayland compositor offers none of the virtual keyboard protocols
+    /// enigo uses. Access requests through the Wayland remote desktop portal
+    /// are not reported as errors by enigo. With the `uinput` backend it
+    /// means `/dev/uinput` is not writable. When several backends were tried,
+    /// it is enough for one of them to be refused.
+    pub fn is_permission_denied(&self) -> bool {
+        match self {
+            Self::NoBackend(attempts) => attempts.iter().any(|(_, e)| e.is_permission_denied()),
+            Self::Uinput(kind) => *kind == std::io::ErrorKind::PermissionDenied,
+            _ => matches!(
+                self,
+                Self::PermissionDenied | Self::Connection(NewConError::EstablishCon(WAYLAND_NO_PROTOCOL))
+            ),
+        }
+    }
+
+    /// Write the message without the "SimulateError: " prefix
+    fn fmt_message(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Connection(e) => write!(f, "Failed to create Enigo instance: {}", e),
+            Self::NoDisplayServer(e) => write!(
+                f,
+                "No display server found: neither DISPLAY nor WAYLAND_DISPLAY is set, \
+                 so there is no X11 or Wayland session to send keys to ({})",
+                e
+            ),
+            Self::PermissionDenied => f.write_str(
+                "The application does not have the permission to simulate input; \
+                 on macOS grant it in System Settings > Privacy & Security > Accessibility",
+            ),
+            Self::TargetElevated => f.write_str(
+                "The foreground window runs with higher privileges (e.g. as administrator) \
+                 and would ignore the keys; run this program elevated as well",
+            ),
+            Self::BackendTimeout(timeout) => {
+                write!(f, "Input backend did not come up within {:?}", timeout)
+            }
+            Self::ReleaseTimeout(timeout) => {
+                write!(f, "Modifier keys were still held down after {:?}", timeout)
+            }
+            Self::NoBackend(attempts) => {
+                f.write_str("No input backend could be created")?;
+                for (i, (name, e)) in attempts.iter().enumerate() {
+                    f.write_str(if i == 0 { ": " } else { "; " })?;
+                    write!(f, "{}: ", name)?;
+                    e.fmt_message(f)?;
+                }
+                Ok(())
+            }
+            Self::Uinput(std::io::ErrorKind::PermissionDenied) => f.write_str(
+                "/dev/uinput is not writable; add the user to the group that owns it \
+                 (often 'input') or install a udev rule that grants access",
+            ),
+            Self::Uinput(std::io::ErrorKind::NotFound) => {
+                f.write_str("/dev/uinput does not exist; load the uinput kernel module")
+            }
+            Self::Uinput(kind) => write!(f, "Failed to open /dev/uinput: {}", kind),
+            Self::Backend(e) => write!(f, "Failed to simulate key event: {}", e),
+        }
+    }
+}
+
+/// Append a " (did you mean ...?)" hint if there is a suggestion
+fn fmt_suggestion(f: &mut fmt::Formatter<'_>, suggestion: &Option<String>) -> fmt::Result {
+    match suggestion {
+        Some(suggestion) => write!(f, " (did you mean {:?}?)", suggestion),
+        None => Ok(()),
+    }
+}
+
+impl fmt::Display for ParseKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ParseKeyError: ")?;
+        self.fmt_message(f)
+    }
+}
+
+impl Error for ParseKeyError {
+    /// The enigo error behind a backend failure, or the error of an invalid
+    /// sequence entry, macro line or macro step
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Simulate(e) => e.source(),
+            Self::InvalidEntry { error, .. } | Self::InvalidLine { error, .. } | Self::StepFailed { error, .. } => {
+                Some(error.as_ref())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SimulateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SimulateError: ")?;
+        self.fmt_message(f)
+    }
+}
+
+impl Error for SimulateError {
+    /// The enigo error behind the failure
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Connection(e) | Self::NoDisplayServer(e) => Some(e),
+            Self::Backend(e) => Some(e),
+            Self::PermissionDenied
+            | Self::TargetElevated
+            | Self::BackendTimeout(_)
+            | Self::ReleaseTimeout(_)
+            | Self::NoBackend(_)
+            | Self::Uinput(_) => None,
+        }
+    }
+}
+
+impl From<SimulateError> for ParseKeyError {
+    fn from(e: SimulateError) -> Self {
+        Self::Simulate(e)
+    }
+}
+
+impl From<std::convert::Infallible> for ParseKeyError {
+    fn from(never: std::convert::Infallible) -> Self {
+        match never {}
+    }
+}
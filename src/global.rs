@@ -0,0 +1,248 @@
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::{KeySimulator, ParseKeyError, SimulateError, SimulateOptions};
+
+/// The simulator shared by the free functions, created on first use
+///
+/// The mutex also serializes the free functions, so the key events of two
+/// combinations sent from different threads never interleave.
+#[cfg(not(any(target_os = "macos", feature = "wayland")))]
+static GLOBAL: Mutex<Option<KeySimulator>> = Mutex::new(None);
+
+/// Serializes the free functions, whose simulators live in [`THREAD_SIMULATOR`]
+#[cfg(any(target_os = "macos", feature = "wayland"))]
+static GLOBAL: Mutex<()> = Mutex::new(());
+
+/// Bumped by [`reset_global_simulator`], a thread's simulator of an older
+/// generation is recreated before its next use
+#[cfg(any(target_os = "macos", feature = "wayland"))]
+static GENERATION: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+// The macOS and `wayland` backends cannot move between threads, every thread
+// keeps its own simulator
+#[cfg(any(target_os = "macos", feature = "wayland"))]
+thread_local! {
+    static THREAD_SIMULATOR: std::cell::RefCell<Option<(u64, KeySimulator)>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+/// The options of the shared simulator, `None` for the defaults
+static DEFAULT_OPTIONS: Mutex<Option<SimulateOptions>> = Mutex::new(None);
+
+/// Lock the free functions, a panic while the lock was held leaves nothing
+/// half-done behind that the next caller needs to know about
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Run `f` with the process-wide simulator, creating it on first use
+///
+/// A simulator that cannot be created is not remembered, the next call
+/// tries again.
+#[cfg(not(any(target_os = "macos", feature = "wayland")))]
+pub(crate) fn with_global_simulator<T, E>(f: impl FnOnce(&mut KeySimulator) -> Result<T, E>) -> Result<T, E>
+where
+    E: From<SimulateError>,
+{
+    let mut global = lock(&GLOBAL);
+    let simulator = match &mut *global {
+        Some(simulator) => simulator,
+        empty => empty.insert(KeySimulator::new()?),
+    };
+    simulator.set_simulate_options(default_options());
+    f(simulator)
+}
+
+/// Run `f` with this thread's simulator, creating it on first use
+///
+/// A simulator that cannot be created is not remembered, the next call
+/// tries again.
+#[cfg(any(target_os = "macos", feature = "wayland"))]
+pub(crate) fn with_global_simulator<T, E>(f: impl FnOnce(&mut KeySimulator) -> Result<T, E>) -> Result<T, E>
+where
+    E: From<SimulateError>,
+{
+    use std::sync::atomic::Ordering;
+
+    let _serialized = lock(&GLOBAL);
+    THREAD_SIMULATOR.with_borrow_mut(|slot| {
+        let generation = GENERATION.load(Ordering::Relaxed);
+        if slot.as_ref().is_some_and(|(created, _)| *created != generation) {
+            *slot = None;
+        }
+        let (_, simulator) = match slot {
+            Some(simulator) => simulator,
+            empty => empty.insert((generation, KeySimulator::new()?)),
+        };
+        simulator.set_simulate_options(default_options());
+        f(simulator)
+    })
+}
+
+/// Run `f` with the shared simulator and the result of `parse`
+///
+/// Invalid input is rejected before a backend connection is opened, so a
+/// typo is reported as such even where no keys can be sent, e.g. in CI.
+pub(crate) fn with_parsed<P, T>(
+    parse: impl FnOnce() -> Result<P, ParseKeyError>,
+    f: impl FnOnce(&mut KeySimulator, P) -> Result<T, ParseKeyError>,
+) -> Result<T, ParseKeyError> {
+    let parsed = parse()?;
+    with_global_simulator(|simulator| f(simulator, parsed))
+}
+
+/// Run `f` while no free function sends key events, for the functions that
+/// bring their own simulator, e.g. with custom settings
+pub(crate) fn serialized<T>(f: impl FnOnce() -> T) -> T {
+    let _serialized = lock(&GLOBAL);
+    f()
+}
+
+/// Set the delays and options [`simulate_key`](crate::simulate_key) and the
+/// other free functions send with from now on, in every thread
+///
+/// This is the process-wide counterpart of
+/// [`KeySimulator::set_simulate_options`], e.g. to give every shortcut of an
+/// application a pause between its modifiers and its main key. The defaults
+/// send every event right away. Functions with their own options, like
+/// [`simulate_key_opts`](crate::simulate_key_opts), use those instead for
+/// that one call.
+///
+/// The pause between the modifiers and the main key is
+/// [`pre_key_delay`](SimulateOptions::pre_key_delay), the pause between
+/// any two key events is
+/// [`inter_event_delay`](SimulateOptions::inter_event_delay) and the order
+/// the modifiers are released in is
+/// [`release_order`](SimulateOptions::release_order).
+///
+/// # Examples
+/// ```no_run
+/// use std::time::Duration;
+/// use simulate_key::{set_default_options, simulate_key, SimulateOptions};
+///
+/// set_default_options(
+///     SimulateOptions::new()
+///         .with_pre_key_delay(Duration::from_millis(20))
+///         .with_inter_event_delay(Duration::from_millis(5)),
+/// );
+/// simulate_key("ctrl+c").unwrap();
+/// ```
+pub fn set_default_options(options: SimulateOptions) {
+    *lock(&DEFAULT_OPTIONS) = Some(options);
+}
+
+/// The options set with [`set_default_options`]
+pub fn default_options() -> SimulateOptions {
+    lock(&DEFAULT_OPTIONS).unwrap_or_default()
+}
+
+/// Release every key pressed through the free functions, e.g. with
+/// [`key_down`](crate::key_down), and every modifier, whether it is down or not
+///
+/// A way out when automation left Ctrl or Shift stuck, without having to
+/// find the physical keys. Only release events are sent, so this never
+/// presses anything. Works before any other function was called, the shared
+/// simulator is created if needed.
+///
+/// Keys pressed through a [`KeySimulator`] of your own are only covered by
+/// the modifiers, use [`KeySimulator::release_all`] for those. On macOS and
+/// with the `wayland` feature every thread has its own shared simulator, so
+/// keys pressed with `key_down` in another thread are also only covered by
+/// the modifiers.
+///
+/// # Examples
+/// ```no_run
+/// simulate_key::release_all_keys().unwrap();
+/// ```
+///
+/// # Errors
+/// Returns `SimulateError` if the backend cannot be created or a key event
+/// fails. Every key is still attempted.
+pub fn release_all_keys() -> Result<(), SimulateError> {
+    with_global_simulator(|simulator| simulator.release_all())
+}
+
+/// Drop the simulator shared by [`simulate_key`](crate::simulate_key) and the
+/// other free functions, so the next call opens a new backend connection
+///
+/// The free functions create their simulator on first use and reuse it for
+/// the rest of the process. Call this when that connection has gone bad,
+/// e.g. after the X server or the Wayland compositor restarted and every key
+/// event fails. Keys the simulator still holds are released as it is dropped,
+/// unless [`Settings::release_keys_when_dropped`](crate::Settings) says otherwise.
+///
+/// On macOS and with the `wayland` feature every thread has its own
+/// simulator, because those backends cannot move between threads. The
+/// simulator of the calling thread is dropped right away, those of other
+/// threads before their next use.
+///
+/// # Examples
+/// ```no_run
+/// use simulate_key::{reset_global_simulator, simulate_key};
+///
+/// if simulate_key("ctrl+c").is_err() {
+///     reset_global_simulator();
+///     simulate_key("ctrl+c").unwrap();
+/// }
+/// ```
+pub fn reset_global_simulator() {
+    #[cfg(not(any(target_os = "macos", feature = "wayland")))]
+    {
+        // Drop it after the lock is released, releasing keys can take a while
+        let simulator = lock(&GLOBAL).take();
+        drop(simulator);
+    }
+    #[cfg(any(target_os = "macos", feature = "wayland"))]
+    {
+        let _serialized = lock(&GLOBAL);
+        GENERATION.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        THREAD_SIMULATOR.with_borrow_mut(|slot| *slot = None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_serialized_excludes_other_threads() {
+        let inside = Arc::new(AtomicUsize::new(0));
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let inside = Arc::clone(&inside);
+                thread::spawn(move || {
+                    for _ in 0..5 {
+                        serialized(|| {
+                            assert_eq!(inside.fetch_add(1, Ordering::SeqCst), 0);
+                            thread::sleep(Duration::from_millis(1));
+                            inside.fetch_sub(1, Ordering::SeqCst);
+                        });
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_default_options() {
+        let options = SimulateOptions::new().with_pre_key_delay(Duration::from_millis(20));
+        set_default_options(options);
+        assert_eq!(default_options(), options);
+        set_default_options(SimulateOptions::default());
+        assert_eq!(default_options(), SimulateOptions::default());
+    }
+
+    #[test]
+    fn test_reset_without_simulator() {
+        // Nothing to drop before first use, and a second reset is harmless
+        reset_global_simulator();
+        reset_global_simulator();
+    }
+}
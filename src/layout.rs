@@ -0,0 +1,109 @@
+use enigo::Key;
+
+use crate::KeyCombo;
+
+/// How a single character in a key combination, e.g. the "c" in "ctrl+c",
+/// is sent
+///
+/// # Examples
+/// ```no_run
+/// use simulate_key::{simulate_key_opts, CharMode, SimulateOptions};
+///
+/// let options = SimulateOptions::new().with_char_mode(CharMode::PhysicalKey);
+/// simulate_key_opts("ctrl+c", &options).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum CharMode {
+    /// Send the character itself and let the backend find a way to produce
+    /// it, which may be a key that is temporarily remapped or an event
+    /// without any key
+    #[default]
+    Unicode,
+    /// Press the key that produces the character on the current keyboard
+    /// layout, so applications that read keys instead of characters see a
+    /// genuine key press
+    ///
+    /// The result depends on the layout: "ctrl+z" presses the key labeled Z,
+    /// which is a different physical key on QWERTY and QWERTZ keyboards.
+    /// Characters without a key, e.g. "€" on a US layout, are sent as in
+    /// [`Unicode`](Self::Unicode) mode.
+    ///
+    /// On Windows the layout is asked with `VkKeyScanW` and only characters
+    /// typed without Shift or AltGr are resolved. On macOS the keyboard
+    /// layout of the current input source is searched, including the
+    /// characters typed with Shift. On Linux the X11 and Wayland backends
+    /// already press the key that the keymap has for a character, so this
+    /// mode is the same as [`Unicode`](Self::Unicode) there. The `uinput`
+    /// backend always sends physical keys.
+    PhysicalKey,
+}
+
+/// The key that types `c` without modifiers on the current keyboard layout
+#[cfg(windows)]
+pub(crate) fn physical_key(c: char) -> Option<Key> {
+    use windows::Win32::UI::Input::KeyboardAndMouse::VkKeyScanW;
+
+    let c = u16::try_from(u32::from(c)).ok()?;
+    let scan = unsafe { VkKeyScanW(c) };
+    // The high byte holds the modifiers the character needs, -1 means none
+    let [vk, shift_state] = scan.to_le_bytes();
+    (scan != -1 && shift_state == 0).then_some(Key::Other(u32::from(vk)))
+}
+
+/// The key that types `c`, with or without Shift, on the current keyboard layout
+#[cfg(target_os = "macos")]
+pub(crate) fn physical_key(c: char) -> Option<Key> {
+    use core_graphics::event::CGKeyCode;
+
+    // enigo returns key code 0 (the A key on US layouts) when no key types
+    // the character, so it only counts for the character "a"
+    match CGKeyCode::try_from(Key::Unicode(c)) {
+        Ok(0) if c != 'a' => None,
+        Ok(keycode) => Some(Key::Other(u32::from(keycode))),
+        Err(()) => None,
+    }
+}
+
+/// No key, the Linux backends look characters up in the keymap themselves
+#[cfg(not(any(windows, target_os = "macos")))]
+pub(crate) fn physical_key(_c: char) -> Option<Key> {
+    None
+}
+
+/// `combo` with its single characters replaced by the keys that type them,
+/// see [`CharMode::PhysicalKey`]
+pub(crate) fn physical_combo(combo: &KeyCombo) -> KeyCombo {
+    let keys = combo
+        .keys()
+        .iter()
+        .map(|key| match key {
+            Key::Unicode(c) => physical_key(*c).unwrap_or(*key),
+            _ => *key,
+        })
+        .collect();
+    KeyCombo::with_keys(combo.modifiers().to_vec(), keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_combination;
+
+    #[test]
+    fn test_unmappable_falls_back_to_unicode() {
+        // No key types the euro sign on a US layout
+        if physical_key('€').is_none() {
+            let combo = parse_combination("ctrl+€").unwrap();
+            assert_eq!(physical_combo(&combo).keys(), &[Key::Unicode('€')]);
+        }
+        let combo = parse_combination("ctrl+f5").unwrap();
+        assert_eq!(physical_combo(&combo), combo);
+    }
+
+    #[cfg(not(any(windows, target_os = "macos")))]
+    #[test]
+    fn test_physical_keys_match_unicode() {
+        let combo = parse_combination("ctrl+shift+c").unwrap();
+        assert_eq!(physical_combo(&combo), combo);
+    }
+}
@@ -0,0 +1,162 @@
+use core_graphics::event::{CGEvent, CGEventFlags, CGEventTapLocation, CGEventType, CGKeyCode, EventField};
+use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
+use enigo::{
+    Direction::{self, Press, Release},
+    InputError, InputResult, Key, Keyboard, Settings,
+};
+
+use crate::modifier_rank;
+
+/// Set on every event enigo sends, so the system doesn't merge them
+const NON_COALESCED: u64 = 0x0000_0100;
+
+/// Keyboard adapter that sends modifiers as flags on the key events instead
+/// of as key events of their own, see
+/// [`SimulateOptions::mac_use_flags`](crate::SimulateOptions::mac_use_flags)
+///
+/// Pressing or releasing a modifier only changes the flags. Every other key
+/// is posted with the flags of the modifiers held at that moment, a modifier
+/// that is clicked on its own goes to the wrapped keyboard. After the last
+/// modifier is released, and when the adapter is dropped with modifiers still
+/// held, a flags-changed event without any modifier is posted so the session
+/// is not left with a phantom Cmd.
+pub(crate) struct MacFlags<'a, K: Keyboard> {
+    keyboard: &'a mut K,
+    source: CGEventSource,
+    /// The `EVENT_SOURCE_USER_DATA` marker of the posted events
+    marker: i64,
+    held: Vec<Key>,
+}
+
+impl<'a, K: Keyboard> MacFlags<'a, K> {
+    pub(crate) fn new(keyboard: &'a mut K, settings: &Settings) -> InputResult<Self> {
+        let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState)
+            .map_err(|()| InputError::Simulate("failed creating event source"))?;
+        Ok(Self {
+            keyboard,
+            source,
+            marker: settings
+                .event_source_user_data
+                .unwrap_or(i64::from(crate::EVENT_MARKER)),
+            held: Vec::new(),
+        })
+    }
+
+    /// The flags of the held modifiers
+    fn flags(&self) -> u64 {
+        held_flags(&self.held)
+    }
+
+    fn post(&self, event: Result<CGEvent, ()>, flags: u64) -> InputResult<()> {
+        let event = event.map_err(|()| InputError::Simulate("failed creating the key event"))?;
+        event.set_integer_value_field(EventField::EVENT_SOURCE_USER_DATA, self.marker);
+        event.set_flags(CGEventFlags::from_bits_retain(flags));
+        event.post(CGEventTapLocation::HID);
+        Ok(())
+    }
+
+    /// Post the key with the flags of the held modifiers
+    fn post_key(&self, key: Key, direction: Direction) -> InputResult<()> {
+        let keycode = CGKeyCode::try_from(key).map_err(|()| InputError::InvalidInput("the key has no macOS key code"))?;
+        if direction != Release {
+            // A modifier tapped under other modifiers includes its own flag
+            let flags = self.flags() | modifier_flags(key).unwrap_or(0);
+            self.post(CGEvent::new_keyboard_event(self.source.clone(), keycode, true), flags)?;
+        }
+        if direction != Press {
+            self.post(CGEvent::new_keyboard_event(self.source.clone(), keycode, false), self.flags())?;
+        }
+        Ok(())
+    }
+
+    /// Tell the system that no modifier is held any more
+    fn clear_flags(&self) -> InputResult<()> {
+        let event = CGEvent::new(self.source.clone());
+        if let Ok(event) = &event {
+            event.set_type(CGEventType::FlagsChanged);
+        }
+        self.post(event, NON_COALESCED)
+    }
+}
+
+impl<K: Keyboard> Keyboard for MacFlags<'_, K> {
+    fn fast_text(&mut self, text: &str) -> InputResult<Option<()>> {
+        self.keyboard.fast_text(text)
+    }
+
+    fn key(&mut self, key: Key, direction: Direction) -> InputResult<()> {
+        match (modifier_flags(key), direction) {
+            (Some(_), Press) => {
+                self.held.push(key);
+                Ok(())
+            }
+            (Some(_), Release) => {
+                self.held.retain(|held| *held != key);
+                if self.held.is_empty() {
+                    self.clear_flags()?;
+                }
+                Ok(())
+            }
+            _ if self.held.is_empty() => self.keyboard.key(key, direction),
+            _ => self.post_key(key, direction),
+        }
+    }
+
+    fn raw(&mut self, keycode: u16, direction: Direction) -> InputResult<()> {
+        self.keyboard.raw(keycode, direction)
+    }
+}
+
+impl<K: Keyboard> Drop for MacFlags<'_, K> {
+    fn drop(&mut self) {
+        if !self.held.is_empty() {
+            let _ = self.clear_flags();
+        }
+    }
+}
+
+/// The `CGEventFlags` bits of a held modifier, including the bit for its
+/// side, or `None` for other keys
+fn modifier_flags(key: Key) -> Option<u64> {
+    const SHIFT: u64 = 0x0002_0000;
+    const CONTROL: u64 = 0x0004_0000;
+    const ALTERNATE: u64 = 0x0008_0000;
+    const COMMAND: u64 = 0x0010_0000;
+
+    // The device-dependent bits from IOLLEvent.h, e.g. NX_DEVICELCTLKEYMASK
+    Some(match modifier_rank(key) {
+        0 | 1 => CONTROL | 0x0001,
+        2 => CONTROL | 0x2000,
+        3 | 4 => ALTERNATE | 0x0020,
+        5 => ALTERNATE | 0x0040,
+        6 | 7 => SHIFT | 0x0002,
+        8 => SHIFT | 0x0004,
+        9 | 10 => COMMAND | 0x0008,
+        11 => COMMAND | 0x0010,
+        _ => return None,
+    })
+}
+
+/// The flags of a key event sent while `held` are down
+fn held_flags(held: &[Key]) -> u64 {
+    held.iter()
+        .filter_map(|key| modifier_flags(*key))
+        .fold(NON_COALESCED, |flags, bits| flags | bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_held_flags() {
+        assert_eq!(held_flags(&[]), NON_COALESCED);
+        assert_eq!(held_flags(&[Key::Meta]), NON_COALESCED | 0x0010_0008);
+        assert_eq!(
+            held_flags(&[Key::Meta, Key::Shift]),
+            NON_COALESCED | 0x0010_0008 | 0x0002_0002
+        );
+        assert_eq!(held_flags(&[Key::RControl]), NON_COALESCED | 0x0004_2000);
+        assert_eq!(modifier_flags(Key::Unicode('a')), None);
+    }
+}
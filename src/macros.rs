@@ -0,0 +1,192 @@
+use crate::{KeyCategories, KEY_CATEGORIES, MAX_REPEAT, PLATFORM_KEYS};
+
+/// A key combination that is checked at compile time
+///
+/// Expands to a [`KeyCombo`](crate::KeyCombo) for a string literal, or fails
+/// the build if the literal is not a valid combination, so a typo in a
+/// compiled-in shortcut is caught before the program runs. The check accepts
+/// single characters, the names of [`get_supported_keys`](crate::get_supported_keys)
+/// in any case, `+` and `-` as separators and `*N` repeat suffixes.
+/// Anything else, e.g. custom aliases or the options of
+/// [`ParseOptions`](crate::ParseOptions), needs
+/// [`parse_combination_with`](crate::parse_combination_with) at runtime.
+///
+/// A `KeyCombo` owns its keys and cannot be a `const`. Put it into a
+/// `LazyLock` for a static binding.
+///
+/// # Examples
+/// ```
+/// use simulate_key::{key_combo, Key};
+///
+/// let save = key_combo!("ctrl+s");
+/// assert_eq!(save.modifiers(), &[Key::Control]);
+/// assert_eq!(save.key(), Key::Unicode('s'));
+/// ```
+///
+/// A misspelled key does not compile:
+/// ```compile_fail
+/// let save = simulate_key::key_combo!("ctlr+s");
+/// ```
+#[macro_export]
+macro_rules! key_combo {
+    ($combination:literal) => {{
+        const _: () = ::core::assert!(
+            $crate::__is_valid_combination($combination),
+            ::core::concat!("invalid key combination: ", $combination)
+        );
+        match <$crate::KeyCombo as ::core::str::FromStr>::from_str($combination) {
+            ::core::result::Result::Ok(combo) => combo,
+            ::core::result::Result::Err(e) => ::core::panic!("{}", e),
+        }
+    }};
+}
+
+/// Whether `combination` is a key combination [`key_combo!`] accepts
+///
+/// Every combination accepted here also parses with the default
+/// [`ParseOptions`](crate::ParseOptions), the reverse is not true.
+#[doc(hidden)]
+pub const fn __is_valid_combination(combination: &str) -> bool {
+    let bytes = combination.as_bytes();
+    let len = bytes.len();
+    if len == 0 {
+        return false;
+    }
+    // A bare separator, or a doubled one at the end, is the separator key
+    if len == 1 && is_separator(bytes[0]) {
+        return true;
+    }
+    let end = if len >= 2 && is_separator(bytes[len - 1]) && is_separator(bytes[len - 2]) {
+        len - 2
+    } else {
+        len
+    };
+
+    let mut start = 0;
+    let mut i = 0;
+    while i <= end {
+        if i == end || is_separator(bytes[i]) {
+            if !is_valid_part(bytes, start, i) {
+                return false;
+            }
+            start = i + 1;
+        }
+        i += 1;
+    }
+    true
+}
+
+const fn is_separator(byte: u8) -> bool {
+    byte == b'+' || byte == b'-'
+}
+
+/// Whether `bytes[start..end]` is a key, optionally followed by `*N`
+const fn is_valid_part(bytes: &[u8], start: usize, end: usize) -> bool {
+    let (start, end) = trim(bytes, start, end);
+    if start == end {
+        return false;
+    }
+
+    // Only digits after the last '*' make a repeat, "*" alone is a key
+    let mut star = end;
+    while star > start && bytes[star - 1] != b'*' {
+        star -= 1;
+    }
+    if star > start + 1 && star < end {
+        let mut count: u32 = 0;
+        let mut i = star;
+        while i < end && bytes[i].is_ascii_digit() {
+            if count <= MAX_REPEAT {
+                count = count * 10 + (bytes[i] - b'0') as u32;
+            }
+            i += 1;
+        }
+        if i == end {
+            return count >= 1 && count <= MAX_REPEAT && is_key(bytes, start, star - 1);
+        }
+    }
+    is_key(bytes, start, end)
+}
+
+/// Whether `bytes[start..end]` is a single character or a supported key name
+const fn is_key(bytes: &[u8], start: usize, end: usize) -> bool {
+    let char_len = match bytes[start] {
+        0x00..=0x7f => 1,
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        _ => 4,
+    };
+    end - start == char_len || is_name(bytes, start, end, KEY_CATEGORIES) || is_name(bytes, start, end, PLATFORM_KEYS)
+}
+
+const fn is_name(bytes: &[u8], start: usize, end: usize, categories: KeyCategories) -> bool {
+    let mut c = 0;
+    while c < categories.len() {
+        let names = categories[c].1;
+        let mut n = 0;
+        while n < names.len() {
+            let name = names[n].as_bytes();
+            if name.len() == end - start {
+                let mut i = 0;
+                while i < name.len() && bytes[start + i].to_ascii_lowercase() == name[i] {
+                    i += 1;
+                }
+                if i == name.len() {
+                    return true;
+                }
+            }
+            n += 1;
+        }
+        c += 1;
+    }
+    false
+}
+
+/// `bytes[start..end]` without surrounding ASCII whitespace
+const fn trim(bytes: &[u8], mut start: usize, mut end: usize) -> (usize, usize) {
+    while start < end && bytes[start].is_ascii_whitespace() {
+        start += 1;
+    }
+    while end > start && bytes[end - 1].is_ascii_whitespace() {
+        end -= 1;
+    }
+    (start, end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_supported_keys, parse_combination};
+
+    #[test]
+    fn test_valid_combinations_parse() {
+        let valid = [
+            "ctrl+c", "Ctrl+Shift+T", "ctrl-c", "ctrl + c", "f5", "alt+tab*3", "ctrl++", "ctrl--",
+            "+", "-", "*", "ctrl+*", "é", "ctrl+€", "win", "ctrl+c+v", "ctrl+ctrl+c",
+        ];
+        for combination in valid {
+            assert!(__is_valid_combination(combination), "{}", combination);
+            assert!(parse_combination(combination).is_ok(), "{}", combination);
+        }
+        for name in get_supported_keys() {
+            assert!(__is_valid_combination(name), "{}", name);
+            assert!(__is_valid_combination(&format!("ctrl+{}", name)), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_invalid_combinations() {
+        let invalid = [
+            "", " ", "ctlr+c", "ctrl+", "+c", "ctrl++c", "ctrl+notakey", "tab*0", "tab*1001", "++",
+        ];
+        for combination in invalid {
+            assert!(!__is_valid_combination(combination), "{}", combination);
+        }
+    }
+
+    #[test]
+    fn test_key_combo_macro() {
+        assert_eq!(crate::key_combo!("ctrl+shift+t"), parse_combination("ctrl+shift+t").unwrap());
+        assert_eq!(crate::key_combo!("alt+tab*2").keys().len(), 2);
+    }
+}
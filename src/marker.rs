@@ -0,0 +1,68 @@
+use enigo::Settings;
+
+/// The value every injected event is marked with unless
+/// [`set_event_marker`] chose a different one
+///
+/// On Windows it is stored in the `dwExtraInfo` field of the input events, on
+/// macOS in the `EVENT_SOURCE_USER_DATA` field. A keyboard hook can read it
+/// there and skip events this crate sent, e.g. to avoid a remapper reacting
+/// to its own output.
+pub const EVENT_MARKER: u32 = enigo::EVENT_MARKER;
+
+/// Whether injected events carry a marker on this platform
+///
+/// Only Windows and macOS have a field for it. X11, Wayland and libei events
+/// look exactly like real key presses, so listeners on Linux have to fall
+/// back to heuristics, e.g. ignoring keys that arrive within a few
+/// milliseconds of sending them.
+pub const SUPPORTS_EVENT_MARKER: bool = cfg!(any(target_os = "windows", target_os = "macos"));
+
+/// Mark every event sent with `settings` with `marker` instead of
+/// [`EVENT_MARKER`]
+///
+/// Has no effect on platforms without [event markers](SUPPORTS_EVENT_MARKER).
+///
+/// # Examples
+/// ```no_run
+/// use simulate_key::{set_event_marker, KeySimulator, Settings};
+///
+/// let mut settings = Settings::default();
+/// set_event_marker(&mut settings, 0x5349_4d4b);
+/// let mut simulator = KeySimulator::with_settings(&settings).unwrap();
+/// simulator.press("ctrl+c").unwrap();
+/// ```
+pub fn set_event_marker(settings: &mut Settings, marker: u32) {
+    settings.windows_dw_extra_info = Some(marker as usize);
+    settings.event_source_user_data = Some(i64::from(marker));
+}
+
+/// Whether the marker field of a received event, e.g. `dwExtraInfo` in a
+/// Windows keyboard hook, says it was sent with the default [`EVENT_MARKER`]
+///
+/// With a custom marker, compare the field with that marker instead.
+///
+/// # Examples
+/// ```
+/// use simulate_key::{is_simulated_event, EVENT_MARKER};
+///
+/// assert!(is_simulated_event(u64::from(EVENT_MARKER)));
+/// assert!(!is_simulated_event(0));
+/// ```
+pub fn is_simulated_event(marker_field: u64) -> bool {
+    marker_field == u64::from(EVENT_MARKER)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_event_marker() {
+        let mut settings = Settings::default();
+        set_event_marker(&mut settings, 42);
+        assert_eq!(settings.windows_dw_extra_info, Some(42));
+        assert_eq!(settings.event_source_user_data, Some(42));
+        assert!(!is_simulated_event(42));
+        assert!(is_simulated_event(u64::from(EVENT_MARKER)));
+    }
+}
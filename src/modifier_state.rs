@@ -0,0 +1,169 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use enigo::Key;
+
+use crate::global::with_global_simulator;
+use crate::SimulateError;
+
+/// How often [`wait_for_release`] looks at the keyboard
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The modifiers the user holds down on the physical keyboard, `None` where
+/// they cannot be queried
+///
+/// On Windows every side-specific modifier is queried with
+/// `GetAsyncKeyState`, on macOS the flags of the HID system state are read,
+/// which only tell Shift, Control, Option and Command apart, not their sides.
+/// Linux has no portable way to ask. The state is only a snapshot, the user
+/// can press or release a key right after.
+pub(crate) fn pressed_modifiers() -> Option<Vec<Key>> {
+    #[cfg(windows)]
+    return Some(windows_impl::pressed_modifiers());
+    #[cfg(target_os = "macos")]
+    return Some(macos_impl::pressed_modifiers());
+    #[cfg(not(any(windows, target_os = "macos")))]
+    None
+}
+
+/// Wait until the user has released every modifier on the physical keyboard
+///
+/// Automation triggered by a hotkey usually starts while the user's fingers
+/// are still on it, and a held Ctrl turns the text that is typed next into
+/// shortcuts. This polls the keyboard every 10ms and returns as soon as no
+/// modifier is down. Modifiers the free functions hold themselves, e.g.
+/// after [`key_down`](crate::key_down), don't count. Where the state cannot
+/// be queried, i.e. on Linux, it pauses for the
+/// [`release_fallback_delay`](crate::SimulateOptions::release_fallback_delay)
+/// of the options set with [`set_default_options`](crate::set_default_options)
+/// instead, which is zero unless set.
+/// [`SimulateOptions::wait_for_release`](crate::SimulateOptions::wait_for_release)
+/// does the same before every call that sends keys.
+///
+/// # Examples
+/// ```no_run
+/// use std::time::Duration;
+/// use simulate_key::{type_text, wait_for_release};
+///
+/// // Called from the handler of a Ctrl+Alt+E hotkey
+/// wait_for_release(Duration::from_secs(2)).unwrap();
+/// type_text("user@example.com").unwrap();
+/// ```
+///
+/// # Errors
+/// Returns `SimulateError::ReleaseTimeout` if a modifier is still held when
+/// `timeout` has passed, or `SimulateError` if the shared simulator cannot
+/// be created.
+pub fn wait_for_release(timeout: Duration) -> Result<(), SimulateError> {
+    with_global_simulator(|simulator| simulator.wait_until_released(timeout))
+}
+
+/// Poll `pressed` until it reports no keys, or pause for `fallback` once if
+/// it cannot tell
+pub(crate) fn wait_until_released(
+    timeout: Duration,
+    fallback: Duration,
+    mut pressed: impl FnMut() -> Option<Vec<Key>>,
+) -> Result<(), SimulateError> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match pressed() {
+            None => {
+                thread::sleep(fallback);
+                return Ok(());
+            }
+            Some(keys) if keys.is_empty() => return Ok(()),
+            Some(_) => {}
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(SimulateError::ReleaseTimeout(timeout));
+        }
+        thread::sleep(POLL_INTERVAL.min(deadline - now));
+    }
+}
+
+#[cfg(windows)]
+mod windows_impl {
+    use enigo::Key;
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        GetAsyncKeyState, VIRTUAL_KEY, VK_LCONTROL, VK_LMENU, VK_LSHIFT, VK_LWIN, VK_RCONTROL, VK_RMENU,
+        VK_RSHIFT, VK_RWIN,
+    };
+
+    const MODIFIERS: [(VIRTUAL_KEY, Key); 8] = [
+        (VK_LCONTROL, Key::LControl),
+        (VK_RCONTROL, Key::RControl),
+        (VK_LMENU, Key::LMenu),
+        (VK_RMENU, Key::RMenu),
+        (VK_LSHIFT, Key::LShift),
+        (VK_RSHIFT, Key::RShift),
+        (VK_LWIN, Key::LWin),
+        (VK_RWIN, Key::RWin),
+    ];
+
+    pub(super) fn pressed_modifiers() -> Vec<Key> {
+        MODIFIERS
+            .iter()
+            // The most significant bit is set while the key is down
+            .filter(|(vk, _)| unsafe { GetAsyncKeyState(i32::from(vk.0)) } < 0)
+            .map(|(_, key)| *key)
+            .collect()
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos_impl {
+    use core_graphics::event::CGEventFlags;
+    use core_graphics::event_source::CGEventSourceStateID;
+    use enigo::Key;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    unsafe extern "C" {
+        fn CGEventSourceFlagsState(state: CGEventSourceStateID) -> CGEventFlags;
+    }
+
+    const MODIFIERS: [(CGEventFlags, Key); 4] = [
+        (CGEventFlags::CGEventFlagControl, Key::Control),
+        (CGEventFlags::CGEventFlagAlternate, Key::Alt),
+        (CGEventFlags::CGEventFlagShift, Key::Shift),
+        (CGEventFlags::CGEventFlagCommand, Key::Meta),
+    ];
+
+    pub(super) fn pressed_modifiers() -> Vec<Key> {
+        let flags = unsafe { CGEventSourceFlagsState(CGEventSourceStateID::HIDSystemState) };
+        MODIFIERS
+            .iter()
+            .filter(|(flag, _)| flags.contains(*flag))
+            .map(|(_, key)| *key)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wait_until_released() {
+        let timeout = Duration::from_secs(5);
+        let mut polls = 0;
+        let released = wait_until_released(timeout, Duration::ZERO, || {
+            polls += 1;
+            Some(if polls < 3 { vec![Key::Control] } else { Vec::new() })
+        });
+        assert_eq!(released, Ok(()));
+        assert_eq!(polls, 3);
+
+        let held = wait_until_released(Duration::from_millis(30), Duration::ZERO, || Some(vec![Key::Shift]));
+        assert_eq!(held, Err(SimulateError::ReleaseTimeout(Duration::from_millis(30))));
+    }
+
+    #[test]
+    fn test_wait_falls_back_to_delay() {
+        let fallback = Duration::from_millis(20);
+        let start = Instant::now();
+        assert_eq!(wait_until_released(Duration::ZERO, fallback, || None), Ok(()));
+        assert!(start.elapsed() >= fallback);
+    }
+}
@@ -0,0 +1,59 @@
+use enigo::{Enigo, NewConError, Settings};
+
+/// Whether this process may simulate input, see [`check_permissions`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PermissionStatus {
+    /// Input can be simulated
+    Granted,
+    /// The permission is missing, on macOS it is granted in System Settings >
+    /// Privacy & Security > Accessibility
+    Denied,
+    /// The platform needs no permission to simulate input
+    NotRequired,
+    /// The backend failed for another reason, so the permission is unknown
+    Unknown,
+}
+
+/// Check whether this process is allowed to simulate input
+///
+/// On macOS simulated keys are dropped until the application was granted the
+/// Accessibility permission. This uses the same check as enigo and, if
+/// `prompt` is true, opens the system dialog that asks the user for the
+/// permission when it is missing. No key is sent. Other platforms return
+/// [`PermissionStatus::NotRequired`].
+///
+/// # Examples
+/// ```no_run
+/// use simulate_key::{check_permissions, PermissionStatus};
+///
+/// if check_permissions(true) == PermissionStatus::Denied {
+///     eprintln!("Allow this app in System Settings > Privacy & Security > Accessibility");
+/// }
+/// ```
+pub fn check_permissions(prompt: bool) -> PermissionStatus {
+    if !cfg!(target_os = "macos") {
+        return PermissionStatus::NotRequired;
+    }
+
+    let settings = Settings {
+        open_prompt_to_get_permissions: prompt,
+        ..Default::default()
+    };
+    match Enigo::new(&settings) {
+        Ok(_) => PermissionStatus::Granted,
+        Err(NewConError::NoPermission) => PermissionStatus::Denied,
+        Err(_) => PermissionStatus::Unknown,
+    }
+}
+
+#[cfg(all(test, not(target_os = "macos")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_not_required_off_macos() {
+        assert_eq!(check_permissions(false), PermissionStatus::NotRequired);
+        assert_eq!(check_permissions(true), PermissionStatus::NotRequired);
+    }
+}
@@ -0,0 +1,158 @@
+use enigo::{Direction, InputResult, Key, Keyboard, Settings};
+
+/// Keyboard adapter that sends keys by their hardware scan code
+///
+/// Many games read the keyboard through DirectInput or Raw Input and ignore
+/// events that only carry a virtual key. On Windows every key is translated
+/// to its scan code with `MapVirtualKeyW` and sent with `KEYEVENTF_SCANCODE`,
+/// plus `KEYEVENTF_EXTENDEDKEY` for the keys that need it, e.g. the arrows,
+/// Insert, Delete and the right Ctrl and Alt. Other platforms already send
+/// hardware key codes, so there the keys pass through unchanged.
+pub(crate) struct Scancodes<'a, K> {
+    keyboard: &'a mut K,
+    /// The `dwExtraInfo` marker of the sent events
+    #[cfg(windows)]
+    extra_info: usize,
+}
+
+impl<'a, K: Keyboard> Scancodes<'a, K> {
+    pub(crate) fn new(keyboard: &'a mut K, settings: &Settings) -> Self {
+        #[cfg(not(windows))]
+        let _ = settings;
+        Self {
+            keyboard,
+            #[cfg(windows)]
+            extra_info: settings
+                .windows_dw_extra_info
+                .unwrap_or(crate::EVENT_MARKER as usize),
+        }
+    }
+}
+
+impl<K: Keyboard> Keyboard for Scancodes<'_, K> {
+    fn fast_text(&mut self, text: &str) -> InputResult<Option<()>> {
+        self.keyboard.fast_text(text)
+    }
+
+    #[cfg(windows)]
+    fn key(&mut self, key: Key, direction: Direction) -> InputResult<()> {
+        let scan = windows_impl::scancode(key)
+            .ok_or(enigo::InputError::InvalidInput("the key has no scan code on this keyboard layout"))?;
+        windows_impl::send(scan, direction, self.extra_info)
+    }
+
+    #[cfg(not(windows))]
+    fn key(&mut self, key: Key, direction: Direction) -> InputResult<()> {
+        self.keyboard.key(key, direction)
+    }
+
+    fn raw(&mut self, keycode: u16, direction: Direction) -> InputResult<()> {
+        self.keyboard.raw(keycode, direction)
+    }
+}
+
+#[cfg(windows)]
+mod windows_impl {
+    use enigo::{Direction, InputError, InputResult, Key};
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        MapVirtualKeyW, SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS,
+        KEYEVENTF_EXTENDEDKEY, KEYEVENTF_KEYUP, KEYEVENTF_SCANCODE, MAPVK_VK_TO_VSC_EX, VIRTUAL_KEY,
+    };
+
+    /// The scan code of `key` on the current keyboard layout, with an `0xE0`
+    /// prefix in the high byte for extended keys
+    pub(super) fn scancode(key: Key) -> Option<u16> {
+        let vk = VIRTUAL_KEY::try_from(key).ok()?;
+        let scan = unsafe { MapVirtualKeyW(u32::from(vk.0), MAPVK_VK_TO_VSC_EX) };
+        u16::try_from(scan).ok().filter(|scan| *scan != 0)
+    }
+
+    /// Send the scan code events for one key with `SendInput`
+    pub(super) fn send(scan: u16, direction: Direction, extra_info: usize) -> InputResult<()> {
+        let mut flags = KEYEVENTF_SCANCODE;
+        if scan >> 8 == 0xE0 {
+            flags |= KEYEVENTF_EXTENDEDKEY;
+        }
+
+        let mut inputs = Vec::with_capacity(2);
+        if direction != Direction::Release {
+            inputs.push(event(scan, flags, extra_info));
+        }
+        if direction != Direction::Press {
+            inputs.push(event(scan, flags | KEYEVENTF_KEYUP, extra_info));
+        }
+
+        let sent = unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) };
+        if sent as usize == inputs.len() {
+            Ok(())
+        } else {
+            Err(InputError::Simulate("not all scan code events could be sent"))
+        }
+    }
+
+    fn event(scan: u16, flags: KEYBD_EVENT_FLAGS, extra_info: usize) -> INPUT {
+        INPUT {
+            r#type: INPUT_KEYBOARD,
+            Anonymous: INPUT_0 {
+                ki: KEYBDINPUT {
+                    wVk: VIRTUAL_KEY(0),
+                    // The prefix is passed as KEYEVENTF_EXTENDEDKEY instead
+                    wScan: scan & 0xff,
+                    dwFlags: flags,
+                    time: 0,
+                    dwExtraInfo: extra_info,
+                },
+            },
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_scancodes() {
+            // Identical on every common layout
+            assert_eq!(scancode(Key::Escape), Some(0x01));
+            assert_eq!(scancode(Key::Space), Some(0x39));
+            assert_eq!(scancode(Key::F1), Some(0x3B));
+            assert_eq!(scancode(Key::UpArrow), Some(0xE048));
+            assert_eq!(scancode(Key::Delete), Some(0xE053));
+            assert_eq!(scancode(Key::RControl), Some(0xE01D));
+        }
+    }
+}
+
+#[cfg(all(test, not(windows)))]
+mod tests {
+    use super::*;
+
+    struct Recorder(Vec<(Key, Direction)>);
+
+    impl Keyboard for Recorder {
+        fn fast_text(&mut self, _: &str) -> InputResult<Option<()>> {
+            Ok(None)
+        }
+
+        fn key(&mut self, key: Key, direction: Direction) -> InputResult<()> {
+            self.0.push((key, direction));
+            Ok(())
+        }
+
+        fn raw(&mut self, _: u16, _: Direction) -> InputResult<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_keys_pass_through() {
+        let mut recorder = Recorder(Vec::new());
+        let mut keyboard = Scancodes::new(&mut recorder, &Settings::default());
+        keyboard.key(Key::Control, Direction::Press).unwrap();
+        keyboard.key(Key::Unicode('w'), Direction::Click).unwrap();
+        assert_eq!(
+            recorder.0,
+            vec![(Key::Control, Direction::Press), (Key::Unicode('w'), Direction::Click)]
+        );
+    }
+}
@@ -0,0 +1,325 @@
+use std::time::Duration;
+
+use crate::global::with_parsed;
+use crate::{offset_in, parse_combination_with, KeyCombo, KeySimulator, ParseKeyError, ParseOptions, MAX_WAIT_MS};
+
+/// One line of a macro script parsed by [`parse_macro`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MacroStep {
+    /// Click a key combination, e.g. `ctrl+c`
+    Combo(KeyCombo),
+    /// Hold a key combination for a while, e.g. `hold space 500`
+    Hold(KeyCombo, Duration),
+    /// Type a string, e.g. `text Hello World`
+    Text(String),
+    /// Pause before the next step, e.g. `wait 1000`
+    Wait(Duration),
+}
+
+/// Parse a line-based macro script
+///
+/// Every line is one of
+/// - a key combination: `ctrl+c`
+/// - a hold with a duration in milliseconds: `hold space 500`
+/// - text to type: `text Hello World`
+/// - a pause in milliseconds: `wait 1000`
+///
+/// Blank lines and lines starting with `#` are ignored, so a lone `#` key has
+/// to be written as `shift+3` or typed with `text #`. Holds and pauses are
+/// limited to [`MAX_WAIT_MS`]. The whole script is validated before anything
+/// is returned.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use simulate_key::{parse_macro, MacroStep};
+///
+/// let steps = parse_macro("# select all and copy\nctrl+a\nwait 200\nctrl+c").unwrap();
+/// assert_eq!(steps.len(), 3);
+/// assert_eq!(steps[1], MacroStep::Wait(Duration::from_millis(200)));
+/// ```
+///
+/// # Errors
+/// Returns `ParseKeyError` naming the line number and text of the first line
+/// that cannot be parsed
+pub fn parse_macro(script: &str) -> Result<Vec<MacroStep>, ParseKeyError> {
+    parse_macro_with(script, &ParseOptions::default())
+}
+
+/// Parse a macro script with custom parse options
+pub(crate) fn parse_macro_with(script: &str, options: &ParseOptions) -> Result<Vec<MacroStep>, ParseKeyError> {
+    script
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(i, line)| {
+            parse_line(line, options).map_err(|e| ParseKeyError::InvalidLine {
+                line: i + 1,
+                text: line.to_string(),
+                error: Box::new(e.offset(offset_in(script, line))),
+            })
+        })
+        .collect()
+}
+
+/// Parse a single trimmed, non-empty line
+fn parse_line(line: &str, options: &ParseOptions) -> Result<MacroStep, ParseKeyError> {
+    let (command, rest) = match line.split_once(char::is_whitespace) {
+        Some((command, rest)) => (command, rest),
+        None => (line, &line[line.len()..]),
+    };
+
+    match command.to_lowercase().as_str() {
+        "text" => Ok(MacroStep::Text(rest.to_string())),
+        "wait" => {
+            let rest = rest.trim();
+            let duration = parse_duration(rest).map_err(|e| e.offset(offset_in(line, rest)))?;
+            Ok(MacroStep::Wait(duration))
+        }
+        "hold" => {
+            let rest = rest.trim();
+            let (combo, ms) = rest.rsplit_once(char::is_whitespace).unwrap_or((&rest[..0], rest));
+            let ms = ms.trim();
+            let duration = parse_duration(ms).map_err(|e| e.offset(offset_in(line, ms)))?;
+            let combo = combo.trim();
+            let combo = parse_combination_with(combo, options).map_err(|e| e.offset(offset_in(line, combo)))?;
+            Ok(MacroStep::Hold(combo, duration))
+        }
+        _ => parse_combination_with(line, options).map(MacroStep::Combo),
+    }
+}
+
+/// A macro built in code and run through one simulator
+///
+/// Steps are queued with the builder methods and run in order by
+/// [`run`](Self::run), without `thread::sleep` calls in between. Every key
+/// combination is parsed before the first key is sent, so a typo in a late
+/// step fails before anything happens.
+///
+/// # Examples
+/// ```no_run
+/// use simulate_key::Macro;
+///
+/// Macro::new()
+///     .press("ctrl+c")
+///     .wait(100)
+///     .hold("space", 500)
+///     .text("hi")
+///     .run()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[must_use = "a macro does nothing until it is run"]
+pub struct Macro {
+    steps: Vec<PendingStep>,
+}
+
+/// A step of a [`Macro`] before its key combination is parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PendingStep {
+    Press(String),
+    Hold(String, Duration),
+    Text(String),
+    Wait(Duration),
+}
+
+impl Macro {
+    /// An empty macro
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Click a key combination, see [`simulate_key`](crate::simulate_key)
+    pub fn press(mut self, combo: &str) -> Self {
+        self.steps.push(PendingStep::Press(combo.to_string()));
+        self
+    }
+
+    /// Hold a key combination for `ms` milliseconds, see
+    /// [`simulate_key_hold`](crate::simulate_key_hold)
+    pub fn hold(mut self, combo: &str, ms: u64) -> Self {
+        self.steps.push(PendingStep::Hold(combo.to_string(), Duration::from_millis(ms)));
+        self
+    }
+
+    /// Type a string, see [`type_text`](crate::type_text)
+    pub fn text(mut self, text: &str) -> Self {
+        self.steps.push(PendingStep::Text(text.to_string()));
+        self
+    }
+
+    /// Pause for `ms` milliseconds before the next step
+    pub fn wait(mut self, ms: u64) -> Self {
+        self.steps.push(PendingStep::Wait(Duration::from_millis(ms)));
+        self
+    }
+
+    /// The number of queued steps
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Whether no step was queued
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Parse every step without sending anything
+    ///
+    /// # Errors
+    /// Returns `ParseKeyError::StepFailed` with the index of the first step
+    /// that cannot be parsed
+    pub fn parse(&self) -> Result<Vec<MacroStep>, ParseKeyError> {
+        self.parse_with(&ParseOptions::default())
+    }
+
+    /// Parse every step with custom parse options
+    fn parse_with(&self, options: &ParseOptions) -> Result<Vec<MacroStep>, ParseKeyError> {
+        self.steps
+            .iter()
+            .enumerate()
+            .map(|(index, step)| {
+                let step = match step {
+                    PendingStep::Press(combo) => parse_combination_with(combo, options).map(MacroStep::Combo),
+                    PendingStep::Hold(combo, duration) => {
+                        parse_combination_with(combo, options).map(|combo| MacroStep::Hold(combo, *duration))
+                    }
+                    PendingStep::Text(text) => Ok(MacroStep::Text(text.clone())),
+                    PendingStep::Wait(duration) => Ok(MacroStep::Wait(*duration)),
+                };
+                step.map_err(|e| ParseKeyError::StepFailed {
+                    index,
+                    error: Box::new(e),
+                })
+            })
+            .collect()
+    }
+
+    /// Run the steps in order through the simulator shared by the free
+    /// functions
+    ///
+    /// # Errors
+    /// Returns `ParseKeyError::StepFailed` with the index of the step that
+    /// could not be parsed or sent. Nothing is sent if a step can't be parsed,
+    /// the steps after a failed one are not run.
+    pub fn run(&self) -> Result<(), ParseKeyError> {
+        with_parsed(|| self.parse(), |simulator, steps| simulator.run_indexed_steps(&steps))
+    }
+
+    /// Run the steps in order through `simulator`, with its parse options
+    /// and delays
+    ///
+    /// # Errors
+    /// See [`run`](Self::run)
+    pub fn run_with(&self, simulator: &mut KeySimulator) -> Result<(), ParseKeyError> {
+        let steps = self.parse_with(simulator.parse_options())?;
+        simulator.run_indexed_steps(&steps)
+    }
+}
+
+/// Parse a number of milliseconds up to [`MAX_WAIT_MS`]
+fn parse_duration(ms: &str) -> Result<Duration, ParseKeyError> {
+    match ms.parse::<u64>() {
+        Ok(value) if value <= MAX_WAIT_MS => Ok(Duration::from_millis(value)),
+        _ => Err(ParseKeyError::InvalidWait {
+            token: ms.to_string(),
+            span: 0..ms.len(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_combination;
+    use enigo::Key;
+
+    fn combo(input: &str) -> KeyCombo {
+        parse_combination(input).unwrap()
+    }
+
+    #[test]
+    fn test_parse_macro() {
+        let script = "
+            # copy the selection
+            ctrl+c
+
+            hold space 500
+            Text Hello World
+            wait 1000
+            alt + tab
+        ";
+        assert_eq!(
+            parse_macro(script).unwrap(),
+            vec![
+                MacroStep::Combo(combo("ctrl+c")),
+                MacroStep::Hold(combo("space"), Duration::from_millis(500)),
+                MacroStep::Text("Hello World".to_string()),
+                MacroStep::Wait(Duration::from_millis(1000)),
+                MacroStep::Combo(KeyCombo::new(vec![Key::Alt], Key::Tab)),
+            ]
+        );
+        assert_eq!(
+            parse_macro("hold ctrl + shift 20").unwrap(),
+            vec![MacroStep::Hold(combo("ctrl+shift"), Duration::from_millis(20))]
+        );
+        assert_eq!(parse_macro("# nothing to do\n\n").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_invalid_lines() {
+        let err = parse_macro("ctrl+a\n# comment\nctrl+notakey\nctrl+v").unwrap_err();
+        assert!(matches!(err, ParseKeyError::InvalidLine { line: 3, .. }), "{}", err);
+        assert!(err.to_string().starts_with("ParseKeyError: Line 3 'ctrl+notakey': Unknown key"), "{}", err);
+        assert_eq!(err.span(), Some(22..29));
+
+        for (script, span) in [
+            ("wait", Some(4..4)),
+            ("wait soon", Some(5..9)),
+            ("wait 60001", Some(5..10)),
+            ("hold space", Some(5..10)),
+            ("hold ctrl+x abc", Some(12..15)),
+            ("hold  50", None),
+            ("hold ctlr+c 50", Some(5..9)),
+        ] {
+            let err = parse_macro(script).unwrap_err();
+            assert!(matches!(err, ParseKeyError::InvalidLine { line: 1, .. }), "{}", script);
+            assert_eq!(err.span(), span, "{}", script);
+        }
+    }
+
+    #[test]
+    fn test_macro_builder() {
+        let steps = Macro::new()
+            .press("ctrl+c")
+            .wait(100)
+            .hold("space", 500)
+            .text("hi")
+            .parse()
+            .unwrap();
+        assert_eq!(
+            steps,
+            vec![
+                MacroStep::Combo(combo("ctrl+c")),
+                MacroStep::Wait(Duration::from_millis(100)),
+                MacroStep::Hold(combo("space"), Duration::from_millis(500)),
+                MacroStep::Text("hi".to_string()),
+            ]
+        );
+        assert!(Macro::new().is_empty());
+        assert_eq!(Macro::new().press("a").wait(10).len(), 2);
+    }
+
+    #[test]
+    fn test_macro_invalid_step() {
+        let builder = Macro::new().press("ctrl+a").wait(10).hold("ctrl+notakey", 50);
+        let err = builder.parse().unwrap_err();
+        assert!(matches!(err, ParseKeyError::StepFailed { index: 2, .. }), "{}", err);
+        assert!(err.to_string().starts_with("ParseKeyError: Step 2: Unknown key"), "{}", err);
+        assert!(err.is_parse_error());
+        assert_eq!(err.span(), Some(5..12));
+        // Rejected before a backend is created, so this also holds without a display
+        assert_eq!(builder.run(), Err(err));
+    }
+}
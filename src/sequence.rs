@@ -0,0 +1,158 @@
+use std::time::Duration;
+
+use crate::combo::split_chords;
+use crate::{offset_in, parse_combination_with, split_sequence, KeyCombo, ParseKeyError, ParseOptions};
+
+/// Longest pause accepted by a wait token, in milliseconds
+pub const MAX_WAIT_MS: u64 = 60_000;
+
+/// One step of a key sequence parsed by [`parse_key_sequence`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SequenceStep {
+    /// Click a key combination
+    Combo(KeyCombo),
+    /// Pause before the next step
+    Wait(Duration),
+}
+
+/// Parse combinations and wait tokens like "ctrl+c wait:500 ctrl+v"
+///
+/// Entries are separated by whitespace or commas, so "ctrl+a, ctrl+c, end"
+/// works as well. A pause is written as `wait:<ms>` or `~<ms>ms`. Every entry
+/// is validated before anything is returned, so nothing is sent for a
+/// sequence with a typo near the end.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use simulate_key::{parse_key_sequence, KeyCombo, SequenceStep};
+///
+/// let steps = parse_key_sequence("ctrl+c ~200ms ctrl+v").unwrap();
+/// assert_eq!(steps[1], SequenceStep::Wait(Duration::from_millis(200)));
+/// assert_eq!(steps[2], SequenceStep::Combo("ctrl+v".parse::<KeyCombo>().unwrap()));
+/// ```
+///
+/// # Errors
+/// Returns `ParseKeyError` naming the position and text of the first entry
+/// that cannot be parsed
+pub fn parse_key_sequence(input: &str) -> Result<Vec<SequenceStep>, ParseKeyError> {
+    parse_key_sequence_with(input, &ParseOptions::default())
+}
+
+/// Parse a key sequence with custom parse options
+pub(crate) fn parse_key_sequence_with(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<Vec<SequenceStep>, ParseKeyError> {
+    let separators = options.separators();
+    let tokens: Vec<&str> = split_sequence(input, separators)
+        .into_iter()
+        .flat_map(|segment| split_chords(segment, separators))
+        .collect();
+    if tokens.is_empty() {
+        return Err(ParseKeyError::EmptySequence);
+    }
+
+    tokens
+        .into_iter()
+        .enumerate()
+        .map(|(i, token)| {
+            let step = match parse_wait(token) {
+                Some(wait) => wait.map(SequenceStep::Wait),
+                None => parse_combination_with(token, options).map(SequenceStep::Combo),
+            };
+            step.map_err(|e| ParseKeyError::InvalidEntry {
+                position: i + 1,
+                entry: token.to_string(),
+                error: Box::new(e.offset(offset_in(input, token))),
+            })
+        })
+        .collect()
+}
+
+/// Parse a `wait:<ms>` or `~<ms>ms` token, `None` if `token` is not a pause
+fn parse_wait(token: &str) -> Option<Result<Duration, ParseKeyError>> {
+    let lower = token.to_lowercase();
+    let ms = match lower.strip_prefix("wait:") {
+        Some(ms) => ms,
+        None => lower.strip_prefix('~')?.strip_suffix("ms")?,
+    };
+
+    Some(match ms.parse::<u64>() {
+        Ok(ms) if ms <= MAX_WAIT_MS => Ok(Duration::from_millis(ms)),
+        _ => Err(ParseKeyError::InvalidWait {
+            token: token.to_string(),
+            span: 0..token.len(),
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use enigo::Key;
+
+    #[test]
+    fn test_parse_key_sequence() {
+        assert_eq!(
+            parse_key_sequence("ctrl+l wait:200 ctrl+c ~100ms alt+tab"),
+            Ok(vec![
+                SequenceStep::Combo(KeyCombo::new(vec![Key::Control], Key::Unicode('l'))),
+                SequenceStep::Wait(Duration::from_millis(200)),
+                SequenceStep::Combo(KeyCombo::new(vec![Key::Control], Key::Unicode('c'))),
+                SequenceStep::Wait(Duration::from_millis(100)),
+                SequenceStep::Combo(KeyCombo::new(vec![Key::Alt], Key::Tab)),
+            ])
+        );
+        assert_eq!(
+            parse_key_sequence("Wait:0"),
+            Ok(vec![SequenceStep::Wait(Duration::ZERO)])
+        );
+
+        // A tilde on its own is still a key
+        assert_eq!(
+            parse_key_sequence("shift+~"),
+            Ok(vec![SequenceStep::Combo(KeyCombo::new(vec![Key::Shift], Key::Unicode('~')))])
+        );
+
+        assert!(parse_key_sequence("").is_err());
+        assert!(parse_key_sequence("ctrl+c ctrl+notakey").is_err());
+    }
+
+    #[test]
+    fn test_comma_separated_entries() {
+        assert_eq!(
+            parse_key_sequence("ctrl+a, ctrl+c, end, ctrl+v"),
+            Ok(vec![
+                SequenceStep::Combo(KeyCombo::new(vec![Key::Control], Key::Unicode('a'))),
+                SequenceStep::Combo(KeyCombo::new(vec![Key::Control], Key::Unicode('c'))),
+                SequenceStep::Combo(KeyCombo::new(vec![], Key::End)),
+                SequenceStep::Combo(KeyCombo::new(vec![Key::Control], Key::Unicode('v'))),
+            ])
+        );
+        assert_eq!(
+            parse_key_sequence("ctrl+,, wait:10 a"),
+            Ok(vec![
+                SequenceStep::Combo(KeyCombo::new(vec![Key::Control], Key::Unicode(','))),
+                SequenceStep::Wait(Duration::from_millis(10)),
+                SequenceStep::Combo(KeyCombo::new(vec![], Key::Unicode('a'))),
+            ])
+        );
+
+        let err = parse_key_sequence("ctrl+a, ctrl+c, ctrl+notakey, ctrl+v").unwrap_err();
+        assert!(matches!(err, ParseKeyError::InvalidEntry { position: 3, .. }), "{}", err);
+        let source = std::error::Error::source(&err).unwrap();
+        assert_eq!(source.to_string(), "ParseKeyError: Unknown key: \"notakey\"");
+        assert!(err.to_string().starts_with("ParseKeyError: Entry 3 'ctrl+notakey': Unknown key"), "{}", err);
+        assert_eq!(err.span(), Some(21..28));
+    }
+
+    #[test]
+    fn test_invalid_wait_durations() {
+        for token in ["wait:-5", "wait:abc", "wait:", "~1.5ms", "wait:60001", "wait:99999999999999999999"] {
+            let err = parse_key_sequence(&format!("ctrl+c {} ctrl+v", token)).unwrap_err();
+            assert!(err.to_string().contains(token), "{}", err);
+        }
+        assert!(parse_key_sequence("wait:60000").is_ok());
+    }
+}
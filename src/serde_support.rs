@@ -0,0 +1,61 @@
+use std::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+use crate::{parse_combination, KeyCombo};
+
+/// Serialized as the canonical string form, e.g. "ctrl+shift+t"
+impl Serialize for KeyCombo {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Deserialized from a combination string with [`parse_combination`], so
+/// aliases like "Control + Return" are accepted and parse errors become
+/// serde errors
+impl<'de> Deserialize<'de> for KeyCombo {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(KeyComboVisitor)
+    }
+}
+
+struct KeyComboVisitor;
+
+impl Visitor<'_> for KeyComboVisitor {
+    type Value = KeyCombo;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a key combination like \"ctrl+c\"")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<KeyCombo, E> {
+        parse_combination(value).map_err(E::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let combo: KeyCombo = serde_json::from_str("\"Control + Shift + Return\"").unwrap();
+        assert_eq!(combo, parse_combination("ctrl+shift+enter").unwrap());
+        assert_eq!(serde_json::to_string(&combo).unwrap(), "\"ctrl+shift+enter\"");
+
+        let bindings: HashMap<String, KeyCombo> =
+            serde_json::from_str(r#"{"copy": "ctrl+c", "zoom": "ctrl++"}"#).unwrap();
+        assert_eq!(bindings["zoom"].to_string(), "ctrl+plus");
+    }
+
+    #[test]
+    fn test_parse_error_is_serde_error() {
+        let err = serde_json::from_str::<KeyCombo>("\"ctrl+entr\"").unwrap_err();
+        assert!(err.to_string().contains("Unknown key: \"entr\" (did you mean \"enter\"?)"), "{}", err);
+        assert!(serde_json::from_str::<KeyCombo>("42").is_err());
+    }
+}
@@ -0,0 +1,2568 @@
+use std::ffi::OsString;
+#[cfg(target_os = "linux")]
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+
+use enigo::{
+    Direction::{self, Click, Press, Release},
+    Enigo, InputError, InputResult, Key, Keyboard, NewConError, Settings,
+};
+
+use crate::backend::{Backend, Device};
+use crate::combo::parse_chord_sequence_with;
+use crate::elevation::check_target_elevation;
+use crate::layout::{physical_combo, CharMode};
+#[cfg(target_os = "macos")]
+use crate::mac_flags::MacFlags;
+use crate::modifier_state::{pressed_modifiers, wait_until_released};
+use crate::scancode::Scancodes;
+use crate::script::parse_macro_with;
+use crate::timing::{RandomDelay, ReleaseOrder, SimulateOptions};
+#[cfg(all(target_os = "linux", feature = "uinput"))]
+use crate::uinput::UinputKeyboard;
+use crate::sequence::parse_key_sequence_with;
+use crate::{
+    all_modifiers, modifier_rank, parse_combination_with, parse_keys, parse_modifiers, MODIFIER_RANKS, split_sequence, unshifted_key, KeyCombo, MacroStep, ParseKeyError, ParseOptions,
+    SequenceStep, SimulateError,
+};
+
+/// How long [`KeySimulator::new`] and [`KeySimulator::with_settings`] wait
+/// for the input backend to come up
+pub const DEFAULT_BACKEND_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long each key of a [multi-tap](KeySimulator::multi_tap) stays down at least
+pub(crate) const MIN_TAP_HOLD: Duration = Duration::from_millis(15);
+
+/// The shortest pause between the taps of a [multi-tap](KeySimulator::multi_tap)
+pub(crate) const MIN_TAP_GAP: Duration = Duration::from_millis(15);
+
+/// A reusable key simulator that owns a single `Enigo` instance
+///
+/// Creating an `Enigo` instance is expensive and on some backends opens a new
+/// display connection, so construct one `KeySimulator` and reuse it when
+/// sending many key combinations.
+///
+/// # Examples
+/// ```no_run
+/// use simulate_key::KeySimulator;
+///
+/// let mut simulator = KeySimulator::new().unwrap();
+/// simulator.press("ctrl+a").unwrap();
+/// simulator.press("ctrl+c").unwrap();
+/// simulator.hold("space", 500).unwrap();
+/// ```
+///
+/// On Windows and Linux a `KeySimulator` is `Send` and can be moved to a
+/// worker thread. The macOS backend and the Linux `wayland` backend are tied
+/// to the thread they were created on, so use a [`KeySimulatorFactory`]
+/// there, or to give every thread its own simulator with the same
+/// configuration.
+pub struct KeySimulator {
+    device: Device,
+    backend: Backend,
+    settings: Settings,
+    timeout: Duration,
+    options: SimulateOptions,
+    random_delay: Option<RandomDelay>,
+    parse_options: ParseOptions,
+    /// Keys pressed with `key_down` or `hold_modifiers` and not released
+    /// yet, in press order
+    held: Vec<Key>,
+    /// Reads the modifiers held on the physical keyboard, replaced in tests
+    modifier_state: fn() -> Option<Vec<Key>>,
+}
+
+impl KeySimulator {
+    /// Create a new simulator with the default `Enigo` settings
+    ///
+    /// # Errors
+    /// Returns `SimulateError` if the `Enigo` instance cannot be created
+    pub fn new() -> Result<Self, SimulateError> {
+        Self::with_settings(&Settings::default())
+    }
+
+    /// Create a new simulator with custom `Enigo` settings, e.g. to choose a
+    /// display on Linux or to set `release_keys_when_dropped`
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use simulate_key::{KeySimulator, Settings};
+    ///
+    /// let settings = Settings {
+    ///     release_keys_when_dropped: false,
+    ///     ..Default::default()
+    /// };
+    /// let mut simulator = KeySimulator::with_settings(&settings).unwrap();
+    /// simulator.press("ctrl+c").unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// Returns `SimulateError` if the `Enigo` instance cannot be created
+    pub fn with_settings(settings: &Settings) -> Result<Self, SimulateError> {
+        Self::with_timeout(settings, DEFAULT_BACKEND_TIMEOUT)
+    }
+
+    /// Create a new simulator with custom `Enigo` settings, giving up if the
+    /// backend does not come up within `timeout`
+    ///
+    /// On some broken Wayland sessions creating the backend blocks for a
+    /// very long time while waiting on the desktop portal. On Linux the backend
+    /// is therefore created on a helper thread, and
+    /// `SimulateError::BackendTimeout` is returned if it is not ready in time.
+    /// A zero `timeout` waits forever. The `wayland` backend cannot move
+    /// between threads, so with that feature a throw-away connection is made
+    /// on the helper thread first and the real one afterwards on the calling
+    /// thread. Other platforms create the backend directly and ignore the
+    /// timeout.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use simulate_key::{KeySimulator, Settings};
+    ///
+    /// let simulator = KeySimulator::with_timeout(&Settings::default(), Duration::from_secs(1));
+    /// ```
+    ///
+    /// # Errors
+    /// Returns `SimulateError` if the `Enigo` instance cannot be created in time
+    pub fn with_timeout(settings: &Settings, timeout: Duration) -> Result<Self, SimulateError> {
+        let (device, backend) = connect(settings, timeout)?;
+        Ok(Self {
+            device,
+            backend,
+            settings: settings.clone(),
+            timeout,
+            options: SimulateOptions::default(),
+            random_delay: None,
+            parse_options: ParseOptions::default(),
+            held: Vec::new(),
+            modifier_state: pressed_modifiers,
+        })
+    }
+
+    /// The name of the backend key events are sent through: "libei",
+    /// "wayland", "x11rb", "xdo" or "uinput" on Linux, "windows" or "macos"
+    /// elsewhere
+    ///
+    /// When several Linux backend features are enabled, they are tried in the
+    /// order libei, Wayland, X11, uinput and the first one that connects is
+    /// used.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use simulate_key::KeySimulator;
+    ///
+    /// let simulator = KeySimulator::new().unwrap();
+    /// println!("sending keys through {}", simulator.backend_name());
+    /// ```
+    pub fn backend_name(&self) -> &'static str {
+        self.backend.name()
+    }
+
+    /// A factory that creates simulators with the same settings, delays and
+    /// parse options as this one, e.g. one per worker thread
+    pub fn factory(&self) -> KeySimulatorFactory {
+        KeySimulatorFactory {
+            settings: self.settings.clone(),
+            timeout: self.timeout,
+            options: self.options,
+            random_delay: self.random_delay(),
+            parse_options: self.parse_options.clone(),
+        }
+    }
+
+    /// Set a delay that is inserted between consecutive key events
+    ///
+    /// The delay applies between pressing the modifiers, clicking the main key
+    /// and releasing the modifiers. Some remote desktop setups drop events
+    /// that arrive too quickly. Defaults to zero. This is the
+    /// [`inter_event_delay`](SimulateOptions::inter_event_delay) of the
+    /// [simulate options](Self::set_simulate_options).
+    pub fn set_inter_key_delay(&mut self, delay: Duration) {
+        self.options.inter_event_delay = delay;
+    }
+
+    /// The delay inserted between consecutive key events
+    pub fn inter_key_delay(&self) -> Duration {
+        self.options.inter_event_delay
+    }
+
+    /// Set the pauses inserted while sending keys
+    ///
+    /// They apply to everything this simulator sends: combinations, holds,
+    /// repeats, sequences and macros.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use simulate_key::{KeySimulator, SimulateOptions};
+    ///
+    /// let mut simulator = KeySimulator::new().unwrap();
+    /// simulator.set_simulate_options(SimulateOptions::new().with_pre_key_delay(Duration::from_millis(20)));
+    /// simulator.press_sequence("ctrl+a, ctrl+c").unwrap();
+    /// ```
+    pub fn set_simulate_options(&mut self, options: SimulateOptions) {
+        self.options = options;
+    }
+
+    /// The pauses inserted while sending keys
+    pub fn simulate_options(&self) -> &SimulateOptions {
+        &self.options
+    }
+
+    /// Add a random delay between `min` and `max` to every pause between
+    /// consecutive key events
+    ///
+    /// Automation with perfectly constant timing is easy to tell apart from a
+    /// human typing. The random part is added on top of the
+    /// [inter-key delay](Self::set_inter_key_delay) and drawn anew for every
+    /// event.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use simulate_key::KeySimulator;
+    ///
+    /// let mut simulator = KeySimulator::new().unwrap();
+    /// simulator.set_random_delay(Duration::from_millis(30), Duration::from_millis(120));
+    /// simulator.press_sequence("h, e, l, l, o").unwrap();
+    /// ```
+    pub fn set_random_delay(&mut self, min: Duration, max: Duration) {
+        self.random_delay = Some(RandomDelay::unseeded(min, max));
+    }
+
+    /// Like [`set_random_delay`](Self::set_random_delay), but the delays are
+    /// drawn from a generator seeded with `seed`, so the same seed gives the
+    /// same timing every run
+    pub fn set_random_delay_seeded(&mut self, min: Duration, max: Duration, seed: u64) {
+        self.random_delay = Some(RandomDelay::new(min, max, seed));
+    }
+
+    /// Remove the random delay, only the inter-key delay stays
+    pub fn clear_random_delay(&mut self) {
+        self.random_delay = None;
+    }
+
+    /// The range random delays are drawn from, if set
+    pub fn random_delay(&self) -> Option<(Duration, Duration)> {
+        self.random_delay.as_ref().map(RandomDelay::range)
+    }
+
+    /// Set the options used to parse key combinations, e.g. to enable
+    /// [`ParseOptions::implicit_shift`]
+    pub fn set_parse_options(&mut self, options: ParseOptions) {
+        self.parse_options = options;
+    }
+
+    /// The options used to parse key combinations
+    pub fn parse_options(&self) -> &ParseOptions {
+        &self.parse_options
+    }
+
+    /// Register a custom key name for everything this simulator parses, see
+    /// [`ParseOptions::add_alias`]
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use simulate_key::KeySimulator;
+    ///
+    /// let mut simulator = KeySimulator::new().unwrap();
+    /// simulator.add_alias("confirm", "enter").unwrap();
+    /// simulator.press("ctrl+confirm").unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// Returns `ParseKeyError` if `target` is not a key or `alias` is not a
+    /// valid name
+    pub fn add_alias(&mut self, alias: &str, target: &str) -> Result<(), ParseKeyError> {
+        self.parse_options.add_alias(alias, target)
+    }
+
+    /// The keyboard backend, paced by the inter-key and random delays, after
+    /// checking that the foreground window accepts simulated input
+    fn keyboard(&mut self) -> Result<Paced<'_, Device>, SimulateError> {
+        check_target_elevation()?;
+        Ok(self.paced())
+    }
+
+    /// The keyboard backend, paced by the inter-key and random delays
+    fn paced(&mut self) -> Paced<'_, Device> {
+        Paced::new(&mut self.device, self.options).with_random_delay(self.random_delay.as_mut())
+    }
+
+    /// Perform a key combination, see [`simulate_key`](crate::simulate_key)
+    ///
+    /// # Errors
+    /// Returns `ParseKeyError` if the key combination cannot be parsed or a
+    /// key event fails
+    pub fn press(&mut self, combo: &str) -> Result<(), ParseKeyError> {
+        let combo = parse_combination_with(combo, &self.parse_options)?;
+        Ok(self.press_combo(&combo)?)
+    }
+
+    /// Perform an already parsed key combination
+    ///
+    /// # Errors
+    /// Returns `SimulateError` if a key event fails
+    pub fn press_combo(&mut self, combo: &KeyCombo) -> Result<(), SimulateError> {
+        self.wait_for_release()?;
+        self.click(combo)
+    }
+
+    /// Wait for the user to release their modifiers if the options ask for
+    /// it, see [`SimulateOptions::wait_for_release`]
+    ///
+    /// Every public method that sends keys calls this once, after its input
+    /// was parsed. Releasing keys never waits.
+    fn wait_for_release(&self) -> Result<(), SimulateError> {
+        match self.options.wait_for_release {
+            Some(timeout) => self.wait_until_released(timeout),
+            None => Ok(()),
+        }
+    }
+
+    /// Wait up to `timeout` until the user holds no modifier, see
+    /// [`wait_for_release`](crate::wait_for_release)
+    ///
+    /// Modifiers this simulator holds itself, e.g. with
+    /// [`key_down`](Self::key_down) or [`hold_modifiers`](Self::hold_modifiers),
+    /// don't count.
+    pub(crate) fn wait_until_released(&self, timeout: Duration) -> Result<(), SimulateError> {
+        wait_until_released(timeout, self.options.release_fallback_delay, || self.user_modifiers())
+    }
+
+    /// The modifiers held on the physical keyboard that this simulator did
+    /// not press itself, `None` where they cannot be queried
+    ///
+    /// Windows reports simulated presses as well, e.g. the Ctrl of
+    /// [`hold_modifiers`](Self::hold_modifiers), so those are left out.
+    fn user_modifiers(&self) -> Option<Vec<Key>> {
+        let mut pressed = (self.modifier_state)()?;
+        pressed.retain(|key| !self.held.iter().any(|held| same_modifier(*held, *key)));
+        Some(pressed)
+    }
+
+    /// Forget one press of each of `keys`, which were released
+    fn forget_held(&mut self, keys: impl IntoIterator<Item = Key>) {
+        for key in keys {
+            if let Some(i) = self.held.iter().rposition(|k| *k == key) {
+                self.held.remove(i);
+            }
+        }
+    }
+
+    /// Click a parsed combination with the options of this simulator
+    fn click(&mut self, combo: &KeyCombo) -> Result<(), SimulateError> {
+        let physical;
+        let combo = if self.options.char_mode == CharMode::PhysicalKey && self.backend != Backend::Uinput {
+            physical = physical_combo(combo);
+            &physical
+        } else {
+            combo
+        };
+        #[cfg(target_os = "macos")]
+        if self.options.mac_use_flags {
+            check_target_elevation()?;
+            let mut flags = MacFlags::new(&mut self.device, &self.settings).map_err(input_error)?;
+            let mut keyboard = Paced::new(&mut flags, self.options).with_random_delay(self.random_delay.as_mut());
+            return click_combo(&mut keyboard, combo);
+        }
+        if self.options.neutralize_user_modifiers {
+            let user = self.user_modifiers().unwrap_or_default();
+            return click_neutralized(&mut self.keyboard()?, combo, &user);
+        }
+        click_combo(&mut self.keyboard()?, combo)
+    }
+
+    /// Perform a key combination with hardware scan codes, see
+    /// [`simulate_key_scancode`](crate::simulate_key_scancode)
+    ///
+    /// # Errors
+    /// Returns `ParseKeyError` if the key combination cannot be parsed or a
+    /// key event fails
+    pub fn press_scancode(&mut self, combo: &str) -> Result<(), ParseKeyError> {
+        let combo = parse_combination_with(combo, &self.parse_options)?;
+        Ok(self.press_combo_scancode(&combo)?)
+    }
+
+    /// Perform an already parsed key combination with hardware scan codes
+    ///
+    /// # Errors
+    /// Returns `SimulateError` if a key event fails
+    pub fn press_combo_scancode(&mut self, combo: &KeyCombo) -> Result<(), SimulateError> {
+        self.wait_for_release()?;
+        check_target_elevation()?;
+        let mut scancodes = Scancodes::new(&mut self.device, &self.settings);
+        let mut keyboard = Paced::new(&mut scancodes, self.options).with_random_delay(self.random_delay.as_mut());
+        click_combo(&mut keyboard, combo)
+    }
+
+    /// Press and hold a key combination for `ms` milliseconds, see
+    /// [`simulate_key_hold`](crate::simulate_key_hold)
+    ///
+    /// # Errors
+    /// Returns `ParseKeyError` if the key combination cannot be parsed or a
+    /// key event fails
+    pub fn hold(&mut self, combo: &str, ms: u64) -> Result<(), ParseKeyError> {
+        let combo = parse_combination_with(combo, &self.parse_options)?;
+        self.wait_for_release()?;
+        Ok(hold_combo(&mut self.keyboard()?, &combo, Duration::from_millis(ms))?)
+    }
+
+    /// Hold several keys at once for `ms` milliseconds, see
+    /// [`simulate_chord`](crate::simulate_chord)
+    ///
+    /// # Errors
+    /// Returns `ParseKeyError` if any key cannot be parsed or a key event fails
+    pub fn hold_keys(&mut self, keys: &[&str], ms: u64) -> Result<(), ParseKeyError> {
+        let keys = parse_keys(keys)?;
+        self.wait_for_release()?;
+        Ok(hold_all(&mut self.keyboard()?, &keys, Duration::from_millis(ms))?)
+    }
+
+    /// Click a key combination `count` times with `interval_ms` between
+    /// repetitions, see [`simulate_key_repeat`](crate::simulate_key_repeat)
+    ///
+    /// # Errors
+    /// Returns `ParseKeyError` if the key combination cannot be parsed or a
+    /// key event fails
+    pub fn repeat(&mut self, combo: &str, count: u32, interval_ms: u64) -> Result<(), ParseKeyError> {
+        // Resolve the combination once, before the first key event is sent
+        let combo = parse_combination_with(combo, &self.parse_options)?;
+        self.wait_for_release()?;
+        Ok(press_repeated(&mut self.keyboard()?, &combo, count, Duration::from_millis(interval_ms))?)
+    }
+
+    /// Tap a key combination `taps` times in quick succession, see
+    /// [`simulate_key_multi_tap`](crate::simulate_key_multi_tap)
+    ///
+    /// # Errors
+    /// Returns `ParseKeyError` if the key combination cannot be parsed or a
+    /// key event fails
+    pub fn multi_tap(&mut self, combo: &str, taps: u32, gap_ms: u64) -> Result<(), ParseKeyError> {
+        let combo = parse_combination_with(combo, &self.parse_options)?;
+        self.wait_for_release()?;
+        check_target_elevation()?;
+        let mut keyboard = Paced::new(&mut self.device, tap_options(self.options)).with_random_delay(self.random_delay.as_mut());
+        let gap = Duration::from_millis(gap_ms).max(MIN_TAP_GAP);
+        Ok(press_repeated(&mut keyboard, &combo, taps, gap)?)
+    }
+
+    /// Press the keys of a combination without releasing them
+    ///
+    /// Modifiers go down first, then the remaining keys in order. The caller
+    /// is responsible for releasing them again with [`key_up`](Self::key_up)
+    /// using the same combination. Keys that are still held when the
+    /// simulator is dropped, also while unwinding from a panic, are released
+    /// unless `release_keys_when_dropped` is disabled in its `Settings`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use simulate_key::KeySimulator;
+    ///
+    /// let mut simulator = KeySimulator::new().unwrap();
+    /// simulator.key_down("shift").unwrap();
+    /// simulator.press("down*5").unwrap();
+    /// simulator.key_up("shift").unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// Returns `ParseKeyError` if the key combination cannot be parsed or a
+    /// key event fails. Keys pressed before the failure are released again.
+    pub fn key_down(&mut self, combo: &str) -> Result<(), ParseKeyError> {
+        let combo = parse_combination_with(combo, &self.parse_options)?;
+        Ok(self.combo_down(&combo)?)
+    }
+
+    /// Press the keys of an already parsed combination, see [`key_down`](Self::key_down)
+    pub(crate) fn combo_down(&mut self, combo: &KeyCombo) -> Result<(), SimulateError> {
+        self.wait_for_release()?;
+        press_down(&mut self.keyboard()?, combo)?;
+        self.held.extend(combo_keys(combo));
+        Ok(())
+    }
+
+    /// Release the keys of an already parsed combination, see [`key_up`](Self::key_up)
+    pub(crate) fn combo_up(&mut self, combo: &KeyCombo) -> Result<(), SimulateError> {
+        // Releasing must not be blocked, whatever window is in front by now
+        let result = release_up(&mut self.paced(), combo);
+        // Forget the keys even if a release failed, they are not sent again on drop
+        self.forget_held(combo_keys(combo));
+        result
+    }
+
+    /// Press modifiers and keep them held until the returned guard is dropped
+    ///
+    /// Unlike [`key_down`](Self::key_down) the modifiers can't be left
+    /// stuck: the guard releases them in reverse order, even when the code
+    /// in between returns early or panics. See [`ModifierGuard`].
+    ///
+    /// # Errors
+    /// Returns `ParseKeyError` if a name is not a modifier or a key event
+    /// fails. Modifiers pressed before the failure are released again.
+    pub fn hold_modifiers(&mut self, modifiers: &[&str]) -> Result<ModifierGuard<'_>, ParseKeyError> {
+        let modifiers = parse_modifiers(modifiers, &self.parse_options)?;
+        self.wait_for_release()?;
+        press_keys(&mut self.keyboard()?, &modifiers)?;
+        self.held.extend(&modifiers);
+        Ok(ModifierGuard {
+            simulator: self,
+            modifiers,
+        })
+    }
+
+    /// Hold modifiers while `f` runs, e.g. Ctrl while clicking several items
+    ///
+    /// The modifiers are pressed in order, `f` gets the simulator to send
+    /// other keys, and the modifiers are released in reverse order afterwards,
+    /// also when `f` returns an error or panics. Calls can be nested, e.g. to
+    /// add Shift for a part of the keys sent while Ctrl is held. Modifier
+    /// names and aliases are the same as in [`press`](Self::press).
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use simulate_key::KeySimulator;
+    ///
+    /// let mut simulator = KeySimulator::new().unwrap();
+    /// simulator
+    ///     .with_modifiers(&["ctrl"], |simulator| {
+    ///         simulator.press("a")?;
+    ///         simulator.with_modifiers(&["shift"], |simulator| simulator.press("end"))?;
+    ///         simulator.press("c")
+    ///     })
+    ///     .unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// Returns the error of `f`, or a `ParseKeyError` converted into `E` if a
+    /// name is not a modifier or a key event fails. `f` is not run when the
+    /// modifiers could not be pressed.
+    pub fn with_modifiers<T, E>(
+        &mut self,
+        modifiers: &[&str],
+        f: impl FnOnce(&mut KeySimulator) -> Result<T, E>,
+    ) -> Result<T, E>
+    where
+        E: From<ParseKeyError>,
+    {
+        let mut guard = self.hold_modifiers(modifiers)?;
+        let result = f(&mut guard);
+        // The error of `f` wins over a failed release
+        let released = guard.release().map_err(ParseKeyError::from);
+        let value = result?;
+        released?;
+        Ok(value)
+    }
+
+    /// Press the keys of a combination and keep them held until the returned
+    /// guard is dropped
+    ///
+    /// Unlike [`hold`](Self::hold) this does not block: the keys stay down
+    /// while the caller does something else, e.g. Shift while clicking to
+    /// extend a selection. The guard releases them in reverse order when it
+    /// is dropped or [`release`](HeldKey::release)d, also when the code in
+    /// between returns early or panics. See [`HeldKey`].
+    ///
+    /// # Errors
+    /// Returns `ParseKeyError` if the key combination cannot be parsed or a
+    /// key event fails. Keys pressed before the failure are released again.
+    pub fn hold_key(&mut self, combo: &str) -> Result<HeldKey<'_>, ParseKeyError> {
+        let combo = parse_combination_with(combo, &self.parse_options)?;
+        self.combo_down(&combo)?;
+        Ok(HeldKey {
+            simulator: self,
+            combo: Some(combo),
+        })
+    }
+
+    /// Release every key this simulator holds down and every modifier,
+    /// whether it is down or not
+    ///
+    /// A way out when keys got stuck, e.g. after a [`key_down`](Self::key_down)
+    /// without its `key_up`. Only release events are sent, so this never
+    /// presses anything. See [`release_all_keys`](crate::release_all_keys).
+    ///
+    /// # Errors
+    /// Returns `SimulateError` if a key event fails. Every key is still
+    /// attempted.
+    pub fn release_all(&mut self) -> Result<(), SimulateError> {
+        let held = std::mem::take(&mut self.held);
+        // Releasing must not be blocked, whatever window is in front
+        release_everything(&mut self.paced(), &held)
+    }
+
+    /// Release the keys of a combination pressed with [`key_down`](Self::key_down)
+    ///
+    /// Keys are released in the reverse order they were pressed in.
+    ///
+    /// # Errors
+    /// Returns `ParseKeyError` if the key combination cannot be parsed or a
+    /// key event fails
+    pub fn key_up(&mut self, combo: &str) -> Result<(), ParseKeyError> {
+        let combo = parse_combination_with(combo, &self.parse_options)?;
+        Ok(self.combo_up(&combo)?)
+    }
+
+    /// Perform comma-separated key combinations in order, see
+    /// [`simulate_sequence`](crate::simulate_sequence)
+    ///
+    /// # Errors
+    /// Returns the `ParseKeyError` of the first segment that fails
+    pub fn press_sequence(&mut self, input: &str) -> Result<(), ParseKeyError> {
+        let combos = parse_sequence(input, &self.parse_options)?;
+        self.wait_for_release()?;
+        Ok(click_all(&mut self.keyboard()?, &combos)?)
+    }
+
+    /// Perform whitespace-separated chords in order with `gap_ms` between
+    /// them, see [`simulate_chord_sequence`](crate::simulate_chord_sequence)
+    ///
+    /// # Errors
+    /// Returns `ParseKeyError` if any chord cannot be parsed or a key event fails
+    pub fn press_chord_sequence(&mut self, input: &str, gap_ms: u64) -> Result<(), ParseKeyError> {
+        let chords = parse_chord_sequence_with(input, &self.parse_options)?;
+        self.wait_for_release()?;
+        Ok(press_chords(&mut self.keyboard()?, &chords, Duration::from_millis(gap_ms))?)
+    }
+
+    /// Perform combinations and inline pauses in order with `gap_ms` between
+    /// combinations, see [`simulate_key_sequence_with_gap`](crate::simulate_key_sequence_with_gap)
+    ///
+    /// # Errors
+    /// Returns `ParseKeyError` if any entry cannot be parsed or a key event fails
+    pub fn press_key_sequence(&mut self, input: &str, gap_ms: u64) -> Result<(), ParseKeyError> {
+        let steps = parse_key_sequence_with(input, &self.parse_options)?;
+        self.wait_for_release()?;
+        Ok(press_steps(&mut self.keyboard()?, &steps, Duration::from_millis(gap_ms))?)
+    }
+
+    /// Run a line-based macro script, see [`run_macro`](crate::run_macro)
+    ///
+    /// # Errors
+    /// Returns `ParseKeyError` if any line cannot be parsed or a key event fails
+    pub fn run_macro(&mut self, script: &str) -> Result<(), ParseKeyError> {
+        let steps = parse_macro_with(script, &self.parse_options)?;
+        self.wait_for_release()?;
+        Ok(run_macro_steps(&mut self.keyboard()?, &steps)?)
+    }
+
+    /// Run the parsed steps of a [`Macro`](crate::Macro)
+    pub(crate) fn run_indexed_steps(&mut self, steps: &[MacroStep]) -> Result<(), ParseKeyError> {
+        self.wait_for_release()?;
+        run_indexed_steps(&mut self.keyboard()?, steps)
+    }
+
+    /// Type a whole string at once, see [`type_text`](crate::type_text)
+    ///
+    /// # Errors
+    /// Returns `SimulateError` if the text cannot be entered
+    pub fn text(&mut self, text: &str) -> Result<(), SimulateError> {
+        self.wait_for_release()?;
+        check_target_elevation()?;
+        self.device.text(text).map_err(input_error)
+    }
+
+    /// Type a string while holding modifiers, see
+    /// [`type_text_with_modifiers`](crate::type_text_with_modifiers)
+    ///
+    /// # Errors
+    /// Returns `ParseKeyError` if a modifier cannot be parsed or a key event
+    /// fails
+    pub fn text_with_modifiers(&mut self, modifiers: &[&str], text: &str) -> Result<(), ParseKeyError> {
+        let modifiers = parse_modifiers(modifiers, &self.parse_options)?;
+        self.wait_for_release()?;
+        Ok(type_with_modifiers(&mut self.keyboard()?, &modifiers, text)?)
+    }
+
+    /// Type a string one character at a time, waiting `per_char_ms`
+    /// milliseconds between characters, see
+    /// [`type_text_delayed`](crate::type_text_delayed)
+    ///
+    /// # Errors
+    /// Returns `SimulateError` if a key event fails
+    pub fn text_delayed(&mut self, text: &str, per_char_ms: u64) -> Result<(), SimulateError> {
+        self.wait_for_release()?;
+        type_chars(&mut self.keyboard()?, text, Duration::from_millis(per_char_ms))
+    }
+
+    /// Type a string as one clicked combination per character, see
+    /// [`simulate_text_as_keys`](crate::simulate_text_as_keys)
+    ///
+    /// Symbols that need Shift on a US keyboard layout, e.g. "!", are sent as
+    /// Shift plus their base key if
+    /// [`us_shifted_symbols`](ParseOptions::us_shifted_symbols) is set in the
+    /// simulator's [`parse_options`](Self::parse_options), and as the
+    /// character itself otherwise.
+    ///
+    /// # Errors
+    /// Returns `SimulateError` if a key event fails
+    pub fn text_as_keys(&mut self, text: &str) -> Result<(), SimulateError> {
+        // The user only needs to let go once, not before every character
+        self.wait_for_release()?;
+        let us_shifted_symbols = self.parse_options.us_shifted_symbols;
+        text_combos(text, us_shifted_symbols).try_for_each(|combo| self.click(&combo))
+    }
+}
+
+impl Drop for KeySimulator {
+    fn drop(&mut self) {
+        // Keys left down by `key_down` would stay stuck system-wide on
+        // backends that don't release them themselves, e.g. uinput
+        if self.settings.release_keys_when_dropped {
+            let _ = release_keys(&mut self.device, &self.held);
+        }
+    }
+}
+
+/// Modifiers held down by [`KeySimulator::hold_modifiers`]
+///
+/// The modifiers are released in the reverse order they were pressed in when
+/// the guard is dropped, also when the code using it returns early or
+/// panics. The simulator stays usable through the guard.
+///
+/// # Examples
+/// ```no_run
+/// use simulate_key::KeySimulator;
+///
+/// let mut simulator = KeySimulator::new().unwrap();
+/// {
+///     let mut ctrl = simulator.hold_modifiers(&["ctrl"]).unwrap();
+///     ctrl.press("a").unwrap();
+///     ctrl.press("c").unwrap();
+/// } // Ctrl is released here
+/// ```
+#[must_use = "the modifiers are released as soon as the guard is dropped"]
+pub struct ModifierGuard<'a> {
+    simulator: &'a mut KeySimulator,
+    modifiers: Vec<Key>,
+}
+
+impl ModifierGuard<'_> {
+    /// The held modifiers, in the order they were pressed in
+    pub fn modifiers(&self) -> &[Key] {
+        &self.modifiers
+    }
+
+    /// Release the modifiers now instead of on drop
+    ///
+    /// # Errors
+    /// Returns `SimulateError` if a key event fails. Every modifier is still
+    /// attempted.
+    pub fn release(mut self) -> Result<(), SimulateError> {
+        let modifiers = std::mem::take(&mut self.modifiers);
+        let result = release_keys(&mut self.simulator.paced(), &modifiers);
+        self.simulator.forget_held(modifiers);
+        result
+    }
+}
+
+impl std::ops::Deref for ModifierGuard<'_> {
+    type Target = KeySimulator;
+
+    fn deref(&self) -> &KeySimulator {
+        self.simulator
+    }
+}
+
+impl std::ops::DerefMut for ModifierGuard<'_> {
+    fn deref_mut(&mut self) -> &mut KeySimulator {
+        self.simulator
+    }
+}
+
+impl Drop for ModifierGuard<'_> {
+    fn drop(&mut self) {
+        // Best effort, there is nobody to report an error to
+        let modifiers = std::mem::take(&mut self.modifiers);
+        let _ = release_keys(&mut self.simulator.paced(), &modifiers);
+        self.simulator.forget_held(modifiers);
+    }
+}
+
+/// A key combination held down by [`KeySimulator::hold_key`]
+///
+/// The keys are released in the reverse order they were pressed in when the
+/// guard is dropped, also when the code using it returns early or panics.
+/// The simulator stays usable through the guard, e.g. to click other keys
+/// while Shift is held. A combination clicked through the guard that
+/// contains a held key releases that key as well.
+///
+/// The guard borrows the simulator, so it can't outlive it and the keys are
+/// released exactly once.
+///
+/// # Examples
+/// ```no_run
+/// use simulate_key::KeySimulator;
+///
+/// let mut simulator = KeySimulator::new().unwrap();
+/// let mut shift = simulator.hold_key("shift").unwrap();
+/// shift.press("down*3").unwrap(); // Select three lines
+/// shift.release().unwrap();
+/// ```
+///
+/// ```compile_fail
+/// use simulate_key::KeySimulator;
+///
+/// let mut simulator = KeySimulator::new().unwrap();
+/// let shift = simulator.hold_key("shift").unwrap();
+/// drop(simulator);
+/// drop(shift);
+/// ```
+#[must_use = "the keys are released as soon as the guard is dropped"]
+pub struct HeldKey<'a> {
+    simulator: &'a mut KeySimulator,
+    /// `None` once the keys were released
+    combo: Option<KeyCombo>,
+}
+
+impl HeldKey<'_> {
+    /// The held key combination
+    pub fn combo(&self) -> &KeyCombo {
+        self.combo.as_ref().expect("the combination is only taken when the guard is consumed")
+    }
+
+    /// Release the keys now instead of on drop
+    ///
+    /// # Errors
+    /// Returns `SimulateError` if a key event fails. Every key is still
+    /// attempted.
+    pub fn release(mut self) -> Result<(), SimulateError> {
+        match self.combo.take() {
+            Some(combo) => self.simulator.combo_up(&combo),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::ops::Deref for HeldKey<'_> {
+    type Target = KeySimulator;
+
+    fn deref(&self) -> &KeySimulator {
+        self.simulator
+    }
+}
+
+impl std::ops::DerefMut for HeldKey<'_> {
+    fn deref_mut(&mut self) -> &mut KeySimulator {
+        self.simulator
+    }
+}
+
+impl Drop for HeldKey<'_> {
+    fn drop(&mut self) {
+        // Best effort, there is nobody to report an error to
+        if let Some(combo) = self.combo.take() {
+            let _ = self.simulator.combo_up(&combo);
+        }
+    }
+}
+
+/// Creates [`KeySimulator`]s with a shared configuration on any thread
+///
+/// The factory is `Send`, `Sync` and cheap to clone on every platform, so it
+/// can be handed to worker threads that each build their own simulator.
+///
+/// # Examples
+/// ```no_run
+/// use std::time::Duration;
+/// use simulate_key::KeySimulatorFactory;
+///
+/// let mut factory = KeySimulatorFactory::default();
+/// factory.set_inter_key_delay(Duration::from_millis(20));
+///
+/// let worker = std::thread::spawn({
+///     let factory = factory.clone();
+///     move || factory.build().unwrap().press("ctrl+c").unwrap()
+/// });
+/// worker.join().unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct KeySimulatorFactory {
+    settings: Settings,
+    timeout: Duration,
+    options: SimulateOptions,
+    random_delay: Option<(Duration, Duration)>,
+    parse_options: ParseOptions,
+}
+
+impl Default for KeySimulatorFactory {
+    fn default() -> Self {
+        Self::new(Settings::default())
+    }
+}
+
+impl KeySimulatorFactory {
+    /// A factory for simulators with custom `Enigo` settings
+    pub fn new(settings: Settings) -> Self {
+        Self {
+            settings,
+            timeout: DEFAULT_BACKEND_TIMEOUT,
+            options: SimulateOptions::default(),
+            random_delay: None,
+            parse_options: ParseOptions::default(),
+        }
+    }
+
+    /// Set how long to wait for the backend, see [`KeySimulator::with_timeout`]
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Set the delay between key events, see [`KeySimulator::set_inter_key_delay`]
+    pub fn set_inter_key_delay(&mut self, delay: Duration) {
+        self.options.inter_event_delay = delay;
+    }
+
+    /// Set the pauses inserted while sending keys, see
+    /// [`KeySimulator::set_simulate_options`]
+    pub fn set_simulate_options(&mut self, options: SimulateOptions) {
+        self.options = options;
+    }
+
+    /// Set a random delay between key events, see
+    /// [`KeySimulator::set_random_delay`]
+    ///
+    /// Every simulator gets its own unseeded generator.
+    pub fn set_random_delay(&mut self, min: Duration, max: Duration) {
+        self.random_delay = Some((min, max));
+    }
+
+    /// Set the options used to parse key combinations
+    pub fn set_parse_options(&mut self, options: ParseOptions) {
+        self.parse_options = options;
+    }
+
+    /// Create a simulator with this configuration
+    ///
+    /// # Errors
+    /// Returns `SimulateError` if the `Enigo` instance cannot be created
+    pub fn build(&self) -> Result<KeySimulator, SimulateError> {
+        let mut simulator = KeySimulator::with_timeout(&self.settings, self.timeout)?;
+        simulator.set_simulate_options(self.options);
+        if let Some((min, max)) = self.random_delay {
+            simulator.set_random_delay(min, max);
+        }
+        simulator.set_parse_options(self.parse_options.clone());
+        Ok(simulator)
+    }
+}
+
+/// Click an already parsed key combination
+fn click_combo<K: ClickKeyboard>(keyboard: &mut K, combo: &KeyCombo) -> Result<(), SimulateError> {
+    let events = click_events(combo, keyboard.release_order());
+    play(keyboard, &events)
+}
+
+/// Click a combination with the modifiers in `user` released, except those
+/// the combination uses itself
+///
+/// The released modifiers are pressed again afterwards, also when the click
+/// failed, the first error is returned.
+fn click_neutralized<K: ClickKeyboard>(keyboard: &mut K, combo: &KeyCombo, user: &[Key]) -> Result<(), SimulateError> {
+    // Left Shift held by the user is the Shift of "shift+a"
+    let interfering: Vec<Key> = user
+        .iter()
+        .copied()
+        .filter(|key| !combo.modifiers().iter().any(|modifier| same_modifier(*modifier, *key)))
+        .collect();
+
+    let mut released = Vec::with_capacity(interfering.len());
+    let mut result = Ok(());
+    for key in interfering {
+        if let Err(e) = keyboard.key(key, Release) {
+            result = Err(input_error(e));
+            break;
+        }
+        released.push(key);
+    }
+    if result.is_ok() {
+        result = click_combo(keyboard, combo);
+    }
+    for key in released {
+        let pressed = keyboard.key(key, Press);
+        if result.is_ok() {
+            result = pressed.map_err(input_error);
+        }
+    }
+    result
+}
+
+/// Whether `a` and `b` are the same modifier on either side, e.g. Shift and
+/// Left Shift
+fn same_modifier(a: Key, b: Key) -> bool {
+    let (a, b) = (modifier_rank(a), modifier_rank(b));
+    a < MODIFIER_RANKS && a / 3 == b / 3
+}
+
+/// The key events that click a combination: modifier presses in order, a
+/// click of every other key and the modifier releases in `order`
+pub(crate) fn click_events(combo: &KeyCombo, order: ReleaseOrder) -> Vec<(Key, Direction)> {
+    let mut releases = combo.modifiers().to_vec();
+    if order == ReleaseOrder::Reverse {
+        releases.reverse();
+    }
+    combo
+        .modifiers()
+        .iter()
+        .map(|key| (*key, Press))
+        .chain(combo.keys().iter().map(|key| (*key, Click)))
+        .chain(releases.into_iter().map(|key| (key, Release)))
+        .collect()
+}
+
+/// A keyboard that clicks combinations with the [`ReleaseOrder`] of its options
+trait ClickKeyboard: Keyboard {
+    fn release_order(&self) -> ReleaseOrder;
+}
+
+impl<K: Keyboard> ClickKeyboard for Paced<'_, K> {
+    fn release_order(&self) -> ReleaseOrder {
+        self.options.release_order
+    }
+}
+
+/// Send key events in order
+///
+/// If an event fails, the keys that are still held down are released in
+/// reverse order (best effort) before the error is returned.
+fn play<K: Keyboard>(keyboard: &mut K, events: &[(Key, Direction)]) -> Result<(), SimulateError> {
+    let mut held = HeldKeys::new(keyboard);
+    for &(key, direction) in events {
+        match direction {
+            Press => held.press(key),
+            // A key whose release failed is not released a second time
+            Release => {
+                held.forget(key);
+                held.key(key, Release)
+            }
+            Click => held.key(key, Click),
+        }
+        .map_err(input_error)?;
+    }
+    Ok(())
+}
+
+/// Keys pressed through the guard are released in reverse order when it is
+/// dropped, also while unwinding from a panic, so a failure or a panic in the
+/// middle of a combination can't leave a modifier stuck
+///
+/// The keyboard stays usable through the guard.
+struct HeldKeys<'a, K: Keyboard> {
+    keyboard: &'a mut K,
+    keys: Vec<Key>,
+}
+
+impl<'a, K: Keyboard> HeldKeys<'a, K> {
+    fn new(keyboard: &'a mut K) -> Self {
+        Self {
+            keyboard,
+            keys: Vec::new(),
+        }
+    }
+
+    /// Press `key` and release it when the guard is dropped
+    fn press(&mut self, key: Key) -> InputResult<()> {
+        self.keyboard.key(key, Press)?;
+        self.keys.push(key);
+        Ok(())
+    }
+
+    /// Stop releasing the last press of `key` on drop
+    fn forget(&mut self, key: Key) {
+        if let Some(i) = self.keys.iter().rposition(|k| *k == key) {
+            self.keys.remove(i);
+        }
+    }
+
+    /// Release the held keys now
+    ///
+    /// All keys are attempted even if one release fails, the first error is returned.
+    fn release(mut self) -> InputResult<()> {
+        let keys = std::mem::take(&mut self.keys);
+        let mut result = Ok(());
+        for key in keys.into_iter().rev() {
+            let released = self.keyboard.key(key, Release);
+            if result.is_ok() {
+                result = released;
+            }
+        }
+        result
+    }
+
+    /// Leave the held keys pressed after the guard is gone
+    fn keep(mut self) {
+        self.keys.clear();
+    }
+}
+
+impl<K: Keyboard> std::ops::Deref for HeldKeys<'_, K> {
+    type Target = K;
+
+    fn deref(&self) -> &K {
+        self.keyboard
+    }
+}
+
+impl<K: Keyboard> std::ops::DerefMut for HeldKeys<'_, K> {
+    fn deref_mut(&mut self) -> &mut K {
+        self.keyboard
+    }
+}
+
+impl<K: Keyboard> Drop for HeldKeys<'_, K> {
+    fn drop(&mut self) {
+        // Best effort, there is nobody to report an error to
+        for key in self.keys.iter().rev() {
+            let _ = self.keyboard.key(*key, Release);
+        }
+    }
+}
+
+/// Click parsed chords in order, waiting `gap` between them
+fn press_chords<K: ClickKeyboard>(
+    keyboard: &mut K,
+    chords: &[KeyCombo],
+    gap: Duration,
+) -> Result<(), SimulateError> {
+    for (i, chord) in chords.iter().enumerate() {
+        if i > 0 && !gap.is_zero() {
+            std::thread::sleep(gap);
+        }
+        click_combo(keyboard, chord)?;
+    }
+    Ok(())
+}
+
+/// Run parsed sequence steps in order, waiting `gap` between two combinations
+fn press_steps<K: ClickKeyboard>(
+    keyboard: &mut K,
+    steps: &[SequenceStep],
+    gap: Duration,
+) -> Result<(), SimulateError> {
+    let mut clicked = false;
+    for step in steps {
+        match step {
+            SequenceStep::Combo(combo) => {
+                if clicked && !gap.is_zero() {
+                    std::thread::sleep(gap);
+                }
+                click_combo(keyboard, combo)?;
+                clicked = true;
+            }
+            SequenceStep::Wait(duration) => std::thread::sleep(*duration),
+        }
+    }
+    Ok(())
+}
+
+/// Press all `keys`, wait `duration` and release them in reverse order
+fn hold_all<K: Keyboard>(
+    keyboard: &mut K,
+    keys: &[Key],
+    duration: Duration,
+) -> Result<(), SimulateError> {
+    with_pressed(keyboard, keys, |_| {
+        std::thread::sleep(duration);
+        Ok(())
+    })
+    .map_err(input_error)
+}
+
+/// Click a parsed combination `count` times, waiting `interval` between clicks
+fn press_repeated<K: ClickKeyboard>(
+    keyboard: &mut K,
+    combo: &KeyCombo,
+    count: u32,
+    interval: Duration,
+) -> Result<(), SimulateError> {
+    for i in 0..count {
+        if i > 0 && !interval.is_zero() {
+            std::thread::sleep(interval);
+        }
+        click_combo(keyboard, combo)?;
+    }
+    Ok(())
+}
+
+/// `options` with every clicked key held for at least [`MIN_TAP_HOLD`]
+///
+/// A key that goes up in the same instant it went down is dropped or merged
+/// with the next tap by some applications.
+fn tap_options(options: SimulateOptions) -> SimulateOptions {
+    SimulateOptions {
+        key_hold_delay: options.key_hold_delay.max(MIN_TAP_HOLD),
+        ..options
+    }
+}
+
+/// Parse comma-separated key combinations, see [`simulate_sequence`](crate::simulate_sequence)
+fn parse_sequence(input: &str, options: &ParseOptions) -> Result<Vec<KeyCombo>, ParseKeyError> {
+    split_sequence(input, options.separators())
+        .into_iter()
+        .map(|combo| parse_combination_with(combo, options))
+        .collect()
+}
+
+/// Click parsed key combinations in order
+fn click_all<K: ClickKeyboard>(keyboard: &mut K, combos: &[KeyCombo]) -> Result<(), SimulateError> {
+    combos.iter().try_for_each(|combo| click_combo(keyboard, combo))
+}
+
+/// Hold an already parsed key combination for `duration`
+fn hold_combo<K: Keyboard>(keyboard: &mut K, combo: &KeyCombo, duration: Duration) -> Result<(), SimulateError> {
+    with_modifiers(keyboard, combo.modifiers(), |keyboard| {
+        with_pressed(keyboard, combo.keys(), |_| {
+            // Hold for specified duration
+            std::thread::sleep(duration);
+            Ok(())
+        })
+    })
+}
+
+/// Click every character of `text` in order, waiting `delay` between them
+///
+/// Line breaks ("\n" or "\r\n") are sent as Return.
+fn type_chars<K: Keyboard>(keyboard: &mut K, text: &str, delay: Duration) -> Result<(), SimulateError> {
+    for (i, key) in text_keys(text).enumerate() {
+        if i > 0 && !delay.is_zero() {
+            std::thread::sleep(delay);
+        }
+        keyboard.key(key, Click).map_err(input_error)?;
+    }
+    Ok(())
+}
+
+/// The key clicked for every character of `text`, with "\n" and "\r\n" as Return
+fn text_keys(text: &str) -> impl Iterator<Item = Key> + '_ {
+    let mut chars = text.chars().peekable();
+    std::iter::from_fn(move || {
+        loop {
+            return match chars.next()? {
+                '\r' if chars.peek() == Some(&'\n') => continue,
+                '\n' | '\r' => Some(Key::Return),
+                c => Some(Key::Unicode(c)),
+            };
+        }
+    })
+}
+
+/// One combination per character of `text`, with Shift and the base key for
+/// uppercase letters and, if `us_shifted_symbols`, the symbols that need
+/// Shift on a US keyboard layout
+fn text_combos(text: &str, us_shifted_symbols: bool) -> impl Iterator<Item = KeyCombo> + '_ {
+    let options = ParseOptions {
+        implicit_shift: true,
+        us_shifted_symbols,
+        ..ParseOptions::default()
+    };
+    text_keys(text).map(move |key| match key {
+        Key::Unicode(c) => match unshifted_key(c, &options) {
+            Some(base) => KeyCombo::new(vec![Key::Shift], Key::Unicode(base)),
+            None => KeyCombo::new(Vec::new(), key),
+        },
+        _ => KeyCombo::new(Vec::new(), key),
+    })
+}
+
+/// Hold `modifiers` while every character of `text` is clicked
+fn type_with_modifiers<K: Keyboard>(keyboard: &mut K, modifiers: &[Key], text: &str) -> Result<(), SimulateError> {
+    with_modifiers(keyboard, modifiers, |keyboard| {
+        text_keys(text).try_for_each(|key| keyboard.key(key, Click))
+    })
+}
+
+/// Run parsed macro steps in order
+fn run_macro_steps<K: ClickKeyboard>(keyboard: &mut K, steps: &[MacroStep]) -> Result<(), SimulateError> {
+    steps.iter().try_for_each(|step| run_macro_step(keyboard, step))
+}
+
+/// Run parsed macro steps in order, naming the index of the step that failed
+fn run_indexed_steps<K: ClickKeyboard>(keyboard: &mut K, steps: &[MacroStep]) -> Result<(), ParseKeyError> {
+    for (index, step) in steps.iter().enumerate() {
+        run_macro_step(keyboard, step).map_err(|e| ParseKeyError::StepFailed {
+            index,
+            error: Box::new(e.into()),
+        })?;
+    }
+    Ok(())
+}
+
+/// Run a single parsed macro step
+fn run_macro_step<K: ClickKeyboard>(keyboard: &mut K, step: &MacroStep) -> Result<(), SimulateError> {
+    match step {
+        MacroStep::Combo(combo) => click_combo(keyboard, combo),
+        MacroStep::Hold(combo, duration) => hold_combo(keyboard, combo, *duration),
+        MacroStep::Text(text) => keyboard.text(text).map_err(input_error),
+        MacroStep::Wait(duration) => {
+            std::thread::sleep(*duration);
+            Ok(())
+        }
+    }
+}
+
+/// The keys of a combination in press order, modifiers first
+fn combo_keys(combo: &KeyCombo) -> Vec<Key> {
+    combo.modifiers().iter().chain(combo.keys()).copied().collect()
+}
+
+/// Press every key of a combination, modifiers first
+fn press_down<K: Keyboard>(keyboard: &mut K, combo: &KeyCombo) -> Result<(), SimulateError> {
+    press_keys(keyboard, &combo_keys(combo))
+}
+
+/// Press `keys` in order, releasing them again if one of them fails
+fn press_keys<K: Keyboard>(keyboard: &mut K, keys: &[Key]) -> Result<(), SimulateError> {
+    // Don't leave a half-pressed combination behind
+    let mut held = HeldKeys::new(keyboard);
+    for key in keys {
+        held.press(*key).map_err(input_error)?;
+    }
+    held.keep();
+    Ok(())
+}
+
+/// Release every key of a combination in the reverse order of [`press_down`]
+fn release_up<K: Keyboard>(keyboard: &mut K, combo: &KeyCombo) -> Result<(), SimulateError> {
+    release_keys(keyboard, &combo_keys(combo))
+}
+
+/// Release `keys` in reverse order
+///
+/// All keys are attempted even if one release fails, the first error is returned.
+fn release_keys<K: Keyboard>(keyboard: &mut K, keys: &[Key]) -> Result<(), SimulateError> {
+    let mut result = Ok(());
+    for key in keys.iter().rev() {
+        let released = keyboard.key(*key, Release);
+        if result.is_ok() {
+            result = released;
+        }
+    }
+    result.map_err(input_error)
+}
+
+/// Release `held` in reverse order, then every modifier that is not among them
+///
+/// All keys are attempted even if one release fails, the first error is returned.
+fn release_everything<K: Keyboard>(keyboard: &mut K, held: &[Key]) -> Result<(), SimulateError> {
+    let modifiers = all_modifiers().into_iter().filter(|key| !held.contains(key));
+    let mut result = Ok(());
+    for key in held.iter().rev().copied().chain(modifiers) {
+        let released = keyboard.key(key, Release);
+        if result.is_ok() {
+            result = released;
+        }
+    }
+    result.map_err(input_error)
+}
+
+/// Press the modifiers, run `action` and release the modifiers in reverse order
+///
+/// If any key event fails, every modifier pressed so far is still released
+/// (best effort) before the first error is returned.
+fn with_modifiers<K: Keyboard>(
+    keyboard: &mut K,
+    modifiers: &[Key],
+    action: impl FnOnce(&mut K) -> Result<(), InputError>,
+) -> Result<(), SimulateError> {
+    with_pressed(keyboard, modifiers, action).map_err(input_error)
+}
+
+/// Press `keys` in order, run `action` and release them in reverse order
+///
+/// Keys that went down are released even if a later event failed or `action`
+/// panicked.
+fn with_pressed<K: Keyboard>(
+    keyboard: &mut K,
+    keys: &[Key],
+    action: impl FnOnce(&mut K) -> Result<(), InputError>,
+) -> Result<(), InputError> {
+    let mut held = HeldKeys::new(keyboard);
+    let mut result = keys.iter().try_for_each(|key| held.press(*key));
+
+    if result.is_ok() {
+        result = action(&mut held);
+    }
+
+    // Release everything that went down, even if an earlier event failed
+    let released = held.release();
+    result.and(released)
+}
+
+/// Keyboard adapter that waits between key events as the [`SimulateOptions`] ask
+struct Paced<'a, K> {
+    keyboard: &'a mut K,
+    options: SimulateOptions,
+    random_delay: Option<&'a mut RandomDelay>,
+    sleep: fn(&mut K, Duration),
+    started: bool,
+    /// Whether the last event pressed a modifier
+    after_modifier: bool,
+}
+
+impl<'a, K: Keyboard> Paced<'a, K> {
+    fn new(keyboard: &'a mut K, options: SimulateOptions) -> Self {
+        Self {
+            keyboard,
+            options,
+            random_delay: None,
+            sleep: |_, delay| std::thread::sleep(delay),
+            started: false,
+            after_modifier: false,
+        }
+    }
+
+    /// Add a random delay on top of the fixed one
+    fn with_random_delay(mut self, random_delay: Option<&'a mut RandomDelay>) -> Self {
+        self.random_delay = random_delay;
+        self
+    }
+
+    /// Record pauses instead of sleeping
+    #[cfg(test)]
+    fn with_sleep(mut self, sleep: fn(&mut K, Duration)) -> Self {
+        self.sleep = sleep;
+        self
+    }
+
+    /// The pause before the next event
+    fn next_delay(&mut self) -> Duration {
+        match self.random_delay.as_mut() {
+            Some(random_delay) => self.options.inter_event_delay + random_delay.next_delay(),
+            None => self.options.inter_event_delay,
+        }
+    }
+
+    fn wait(&mut self, delay: Duration) {
+        if !delay.is_zero() {
+            (self.sleep)(self.keyboard, delay);
+        }
+    }
+
+    /// Sleep before every event except the first one
+    fn pace(&mut self) {
+        if self.started {
+            let delay = self.next_delay();
+            self.wait(delay);
+        }
+        self.started = true;
+    }
+}
+
+impl<K: Keyboard> Keyboard for Paced<'_, K> {
+    fn fast_text(&mut self, text: &str) -> InputResult<Option<()>> {
+        self.keyboard.fast_text(text)
+    }
+
+    fn key(&mut self, key: Key, direction: Direction) -> InputResult<()> {
+        self.pace();
+        let modifier = modifier_rank(key) < MODIFIER_RANKS;
+        if self.after_modifier && !modifier && direction != Release {
+            self.wait(self.options.pre_key_delay);
+        }
+        self.after_modifier = modifier && direction == Press;
+
+        if direction != Click || self.options.key_hold_delay.is_zero() {
+            return self.keyboard.key(key, direction);
+        }
+
+        self.keyboard.key(key, Press)?;
+        self.wait(self.options.key_hold_delay);
+        // The caller only knows about a click, a key left down here would
+        // stay stuck, so a failed release is tried once more
+        self.keyboard
+            .key(key, Release)
+            .or_else(|e| self.keyboard.key(key, Release).map_err(|_| e))
+    }
+
+    fn raw(&mut self, keycode: u16, direction: Direction) -> InputResult<()> {
+        self.pace();
+        self.after_modifier = false;
+        self.keyboard.raw(keycode, direction)
+    }
+}
+
+/// Connect to the first enabled backend that works
+///
+/// With a single backend its error is returned as is, otherwise every
+/// failed attempt is collected into `SimulateError::NoBackend`.
+fn connect(settings: &Settings, timeout: Duration) -> Result<(Device, Backend), SimulateError> {
+    let backends = Backend::enabled();
+    if let [backend] = backends[..] {
+        return open(backend, settings, timeout).map(|device| (device, backend));
+    }
+    first_working(&backends, settings, |name| std::env::var_os(name), |backend, settings| {
+        open(backend, settings, timeout)
+    })
+}
+
+/// Open the first of `backends` that works in the environment described by
+/// `var`, collecting why each of the others failed
+fn first_working<T>(
+    backends: &[Backend],
+    settings: &Settings,
+    var: impl Fn(&str) -> Option<OsString>,
+    mut open: impl FnMut(Backend, &Settings) -> Result<T, SimulateError>,
+) -> Result<(T, Backend), SimulateError> {
+    let mut attempts = Vec::with_capacity(backends.len());
+    for &backend in backends {
+        if let Some(reason) = backend.unusable(settings, &var) {
+            attempts.push((backend.name(), SimulateError::Connection(NewConError::EstablishCon(reason))));
+            continue;
+        }
+        match open(backend, &backend.restrict(settings)) {
+            Ok(device) => return Ok((device, backend)),
+            Err(e) => attempts.push((backend.name(), e)),
+        }
+    }
+    Err(SimulateError::NoBackend(attempts))
+}
+
+/// Connect to `backend`
+fn open(backend: Backend, settings: &Settings, timeout: Duration) -> Result<Device, SimulateError> {
+    match backend {
+        #[cfg(all(target_os = "linux", feature = "uinput"))]
+        Backend::Uinput => UinputKeyboard::new().map(Device::Uinput),
+        _ => create_enigo(settings, timeout).map(|enigo| Device::Enigo(Box::new(enigo))),
+    }
+}
+
+/// Create the `Enigo` instance, on Linux on a helper thread that is given up
+/// on after `timeout` unless that is zero
+///
+/// The `wayland` backend cannot be moved between threads. There a throw-away
+/// connection on the helper thread proves that the session answers in time,
+/// and the real one is made on this thread afterwards.
+fn create_enigo(settings: &Settings, timeout: Duration) -> Result<Enigo, SimulateError> {
+    #[cfg(target_os = "linux")]
+    if !timeout.is_zero() {
+        let probe = settings.clone();
+        #[cfg(not(feature = "wayland"))]
+        return with_deadline(timeout, move || Enigo::new(&probe));
+        #[cfg(feature = "wayland")]
+        with_deadline(timeout, move || Enigo::new(&probe).map(drop))?;
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = timeout;
+
+    Enigo::new(settings).map_err(connection_error)
+}
+
+/// Run `connect` on a helper thread and wait at most `timeout` for it
+#[cfg(target_os = "linux")]
+fn with_deadline<T: Send + 'static>(
+    timeout: Duration,
+    connect: impl FnOnce() -> Result<T, NewConError> + Send + 'static,
+) -> Result<T, SimulateError> {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        // Nobody is waiting any more after a timeout, the result is dropped here
+        let _ = sender.send(connect());
+    });
+
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => result.map_err(connection_error),
+        Err(RecvTimeoutError::Timeout) => Err(SimulateError::BackendTimeout(timeout)),
+        // enigo panics instead of returning an error in some cases, e.g. when
+        // the Wayland remote desktop portal refuses access
+        Err(RecvTimeoutError::Disconnected) => Err(SimulateError::Connection(NewConError::EstablishCon(
+            "the input backend panicked while connecting",
+        ))),
+    }
+}
+
+/// Wrap an error from creating the `Enigo` instance, telling a missing
+/// permission or display server apart from other connection failures
+fn connection_error(e: NewConError) -> SimulateError {
+    if e == NewConError::NoPermission {
+        SimulateError::PermissionDenied
+    } else if cfg!(target_os = "linux") && !has_display_server(|name| std::env::var_os(name)) {
+        SimulateError::NoDisplayServer(e)
+    } else {
+        SimulateError::Connection(e)
+    }
+}
+
+/// Whether the environment points at an X11 or Wayland session
+fn has_display_server(var: impl Fn(&str) -> Option<OsString>) -> bool {
+    ["DISPLAY", "WAYLAND_DISPLAY"]
+        .iter()
+        .any(|name| var(name).is_some_and(|value| !value.is_empty()))
+}
+
+/// Wrap an error reported by the keyboard backend
+fn input_error(e: InputError) -> SimulateError {
+    SimulateError::Backend(e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    /// Parse and click comma-separated key combinations
+    fn press_sequence<K: ClickKeyboard>(keyboard: &mut K, input: &str, options: &ParseOptions) -> Result<(), ParseKeyError> {
+        let combos = parse_sequence(input, options)?;
+        Ok(click_all(keyboard, &combos)?)
+    }
+
+    /// Parse a key combination and hold it for `ms` milliseconds
+    fn hold_combination<K: Keyboard>(
+        keyboard: &mut K,
+        combo: &str,
+        ms: u64,
+        options: &ParseOptions,
+    ) -> Result<(), ParseKeyError> {
+        let combo = parse_combination_with(combo, options)?;
+        Ok(hold_combo(keyboard, &combo, Duration::from_millis(ms))?)
+    }
+
+    /// Parse and click a key combination
+    fn press_combination<K: ClickKeyboard>(
+        keyboard: &mut K,
+        combo: &str,
+        options: &ParseOptions,
+    ) -> Result<(), ParseKeyError> {
+        let combo = parse_combination_with(combo, options)?;
+        Ok(click_combo(keyboard, &combo)?)
+    }
+
+    /// A simulator on [`Device::Recording`] that reads `modifier_state` as
+    /// the physical keyboard
+    fn recording_simulator(options: SimulateOptions, modifier_state: fn() -> Option<Vec<Key>>) -> KeySimulator {
+        KeySimulator {
+            device: Device::Recording(Vec::new()),
+            backend: Backend::Native,
+            settings: Settings::default(),
+            timeout: Duration::ZERO,
+            options,
+            random_delay: None,
+            parse_options: ParseOptions::default(),
+            held: Vec::new(),
+            modifier_state,
+        }
+    }
+
+    /// The key events a [`recording_simulator`] sent
+    fn recorded(simulator: &KeySimulator) -> &[(Key, Direction)] {
+        match &simulator.device {
+            Device::Recording(events) => events,
+            _ => unreachable!("not a recording simulator"),
+        }
+    }
+
+    /// Keyboard backend that records every key event instead of sending it
+    #[derive(Default)]
+    struct RecordingKeyboard {
+        events: Vec<(Key, Direction)>,
+        fail_on: Option<(Key, Direction)>,
+        /// Events that were rejected because of `fail_on`
+        rejected: Vec<(Key, Direction)>,
+        /// Pauses and the number of events sent before each of them
+        sleeps: Vec<(usize, Duration)>,
+    }
+
+    /// Record a pause of [`Paced`] instead of sleeping
+    fn record_sleep(keyboard: &mut RecordingKeyboard, delay: Duration) {
+        keyboard.sleeps.push((keyboard.events.len(), delay));
+    }
+
+    impl Keyboard for RecordingKeyboard {
+        fn fast_text(&mut self, _text: &str) -> InputResult<Option<()>> {
+            Ok(None)
+        }
+
+        fn key(&mut self, key: Key, direction: Direction) -> InputResult<()> {
+            if self.fail_on == Some((key, direction)) {
+                self.rejected.push((key, direction));
+                return Err(InputError::Simulate("rejected by test backend"));
+            }
+            self.events.push((key, direction));
+            Ok(())
+        }
+
+        fn raw(&mut self, _keycode: u16, _direction: Direction) -> InputResult<()> {
+            Ok(())
+        }
+    }
+
+    impl ClickKeyboard for RecordingKeyboard {
+        fn release_order(&self) -> ReleaseOrder {
+            ReleaseOrder::Reverse
+        }
+    }
+
+    /// Keys that were pressed but never released
+    fn held_keys(events: &[(Key, Direction)]) -> Vec<Key> {
+        let mut held = Vec::new();
+        for (key, direction) in events {
+            match direction {
+                Press => held.push(*key),
+                Release => held.retain(|k| k != key),
+                Click => {}
+            }
+        }
+        held
+    }
+
+    #[test]
+    fn test_run_macro_steps() {
+        let mut keyboard = RecordingKeyboard::default();
+        let steps = parse_macro_with("ctrl+c\nwait 1\ntext hi\nhold shift 1", &ParseOptions::default()).unwrap();
+        run_macro_steps(&mut keyboard, &steps).unwrap();
+        assert_eq!(
+            keyboard.events,
+            vec![
+                (Key::Control, Press),
+                (Key::Unicode('c'), Click),
+                (Key::Control, Release),
+                (Key::Unicode('h'), Click),
+                (Key::Unicode('i'), Click),
+                (Key::Shift, Press),
+                (Key::Shift, Release),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_click_plays_click_events() {
+        let combo = parse_combination_with("ctrl+shift+a+b", &ParseOptions::default()).unwrap();
+        let mut keyboard = RecordingKeyboard::default();
+        click_combo(&mut keyboard, &combo).unwrap();
+        assert_eq!(click_events(&combo, ReleaseOrder::Reverse), keyboard.events);
+        assert!(held_keys(&keyboard.events).is_empty());
+    }
+
+    #[test]
+    fn test_release_everything() {
+        let mut keyboard = RecordingKeyboard {
+            fail_on: Some((Key::Control, Release)),
+            ..Default::default()
+        };
+        let held = [Key::Shift, Key::Unicode('w')];
+        assert!(release_everything(&mut keyboard, &held).is_err());
+        // Nothing is pressed, the held keys go first and every modifier once
+        assert!(keyboard.events.iter().all(|(_, direction)| *direction == Release));
+        assert_eq!(keyboard.events[..2], [(Key::Unicode('w'), Release), (Key::Shift, Release)]);
+        let released: Vec<Key> = keyboard.events.iter().map(|(key, _)| *key).collect();
+        for modifier in all_modifiers() {
+            let count = released.iter().filter(|key| **key == modifier).count();
+            // The failed Ctrl release is not recorded
+            assert_eq!(count, usize::from(modifier != Key::Control), "{:?}", modifier);
+        }
+    }
+
+    #[test]
+    fn test_click_neutralized() {
+        let options = ParseOptions::default();
+        let user = [Key::LShift, Key::Meta];
+        let mut keyboard = RecordingKeyboard::default();
+        let combo = parse_combination_with("ctrl+a", &options).unwrap();
+        click_neutralized(&mut keyboard, &combo, &user).unwrap();
+        assert_eq!(
+            keyboard.events,
+            vec![
+                (Key::LShift, Release),
+                (Key::Meta, Release),
+                (Key::Control, Press),
+                (Key::Unicode('a'), Click),
+                (Key::Control, Release),
+                (Key::LShift, Press),
+                (Key::Meta, Press),
+            ]
+        );
+
+        // The held Shift is the one of the combination, it stays down
+        let mut keyboard = RecordingKeyboard::default();
+        let combo = parse_combination_with("shift+a", &options).unwrap();
+        click_neutralized(&mut keyboard, &combo, &user).unwrap();
+        assert_eq!(keyboard.events[..2], [(Key::Meta, Release), (Key::Shift, Press)]);
+        assert_eq!(keyboard.events.last(), Some(&(Key::Meta, Press)));
+
+        // A failed click still gives the user their modifiers back
+        let mut keyboard = RecordingKeyboard {
+            fail_on: Some((Key::Unicode('a'), Click)),
+            ..Default::default()
+        };
+        let combo = parse_combination_with("ctrl+a", &options).unwrap();
+        assert!(click_neutralized(&mut keyboard, &combo, &user).is_err());
+        assert_eq!(keyboard.events[keyboard.events.len() - 2..], [(Key::LShift, Press), (Key::Meta, Press)]);
+    }
+
+    #[test]
+    fn test_neutralize_keeps_own_modifiers() {
+        // Windows reports the Ctrl pressed by `with_modifiers` as held
+        let options = SimulateOptions::new().with_neutralize_user_modifiers(true);
+        let mut simulator = recording_simulator(options, || Some(vec![Key::LControl]));
+        simulator
+            .with_modifiers(&["ctrl"], |simulator| simulator.press("a"))
+            .unwrap();
+        assert_eq!(
+            recorded(&simulator),
+            [(Key::Control, Press), (Key::Unicode('a'), Click), (Key::Control, Release)]
+        );
+        assert!(simulator.held.is_empty());
+
+        // A Shift the user holds is still released around the click
+        let mut simulator = recording_simulator(options, || Some(vec![Key::LControl, Key::LShift]));
+        simulator
+            .with_modifiers(&["ctrl"], |simulator| simulator.press("a"))
+            .unwrap();
+        assert_eq!(
+            recorded(&simulator),
+            [
+                (Key::Control, Press),
+                (Key::LShift, Release),
+                (Key::Unicode('a'), Click),
+                (Key::LShift, Press),
+                (Key::Control, Release),
+            ]
+        );
+    }
+
+    thread_local! {
+        /// The modifiers [`physical_modifiers`] reports
+        static PHYSICAL: std::cell::RefCell<Vec<Key>> = const { std::cell::RefCell::new(Vec::new()) };
+    }
+
+    /// A physical keyboard state that tests can change
+    fn physical_modifiers() -> Option<Vec<Key>> {
+        Some(PHYSICAL.with_borrow(Vec::clone))
+    }
+
+    #[test]
+    fn test_wait_for_release_before_every_send() {
+        let timeout = Duration::from_millis(30);
+        let options = SimulateOptions::new().with_wait_for_release(timeout);
+        let mut simulator = recording_simulator(options, physical_modifiers);
+
+        // Windows reports the Shift of `key_down` as held, it doesn't block
+        simulator.key_down("shift").unwrap();
+        PHYSICAL.set(vec![Key::LShift]);
+        simulator.press("a").unwrap();
+        simulator.text_delayed("b", 0).unwrap();
+        simulator.key_up("shift").unwrap();
+        let sent = recorded(&simulator).len();
+
+        // Ctrl held by the user blocks every way of sending keys
+        PHYSICAL.set(vec![Key::LControl]);
+        let timed_out = SimulateError::ReleaseTimeout(timeout);
+        assert_eq!(simulator.text("x"), Err(timed_out.clone()));
+        assert_eq!(simulator.text_delayed("x", 0), Err(timed_out.clone()));
+        assert_eq!(simulator.run_macro("text x"), Err(timed_out.clone().into()));
+        assert_eq!(simulator.hold("x", 0), Err(timed_out.clone().into()));
+        assert_eq!(simulator.press_sequence("x, y"), Err(timed_out.into()));
+        assert_eq!(recorded(&simulator).len(), sent);
+
+        // Releasing never waits
+        simulator.release_all().unwrap();
+        PHYSICAL.set(Vec::new());
+    }
+
+    #[test]
+    fn test_release_order() {
+        let combo = parse_combination_with("ctrl+alt+shift+t", &ParseOptions::default()).unwrap();
+        let releases = |events: &[(Key, Direction)]| -> Vec<Key> {
+            events.iter().filter(|(_, direction)| *direction == Release).map(|(key, _)| *key).collect()
+        };
+        assert_eq!(
+            releases(&click_events(&combo, ReleaseOrder::Reverse)),
+            vec![Key::Shift, Key::Alt, Key::Control]
+        );
+        assert_eq!(
+            click_events(&combo, ReleaseOrder::Forward),
+            vec![
+                (Key::Control, Press),
+                (Key::Alt, Press),
+                (Key::Shift, Press),
+                (Key::Unicode('t'), Click),
+                (Key::Control, Release),
+                (Key::Alt, Release),
+                (Key::Shift, Release),
+            ]
+        );
+
+        // The order of the options is what gets sent
+        let options = SimulateOptions::new().with_release_order(ReleaseOrder::Forward);
+        let mut keyboard = RecordingKeyboard::default();
+        click_combo(&mut Paced::new(&mut keyboard, options), &combo).unwrap();
+        assert_eq!(keyboard.events, click_events(&combo, ReleaseOrder::Forward));
+    }
+
+    #[test]
+    fn test_failed_release_of_held_click_is_retried() {
+        let combo = parse_combination_with("ctrl+a", &ParseOptions::default()).unwrap();
+        let options = SimulateOptions::new().with_key_hold_delay(Duration::from_millis(10));
+        let mut keyboard = RecordingKeyboard {
+            fail_on: Some((Key::Unicode('a'), Release)),
+            ..Default::default()
+        };
+        let mut paced = Paced::new(&mut keyboard, options).with_sleep(record_sleep);
+        assert!(click_combo(&mut paced, &combo).is_err());
+        assert_eq!(keyboard.rejected, vec![(Key::Unicode('a'), Release); 2]);
+        assert_eq!(
+            keyboard.events,
+            vec![(Key::Control, Press), (Key::Unicode('a'), Press), (Key::Control, Release)]
+        );
+    }
+
+    #[test]
+    fn test_indexed_steps_name_failed_step() {
+        let steps = crate::Macro::new().press("a").wait(0).press("ctrl+b").press("c").parse().unwrap();
+        let mut keyboard = RecordingKeyboard {
+            fail_on: Some((Key::Unicode('b'), Click)),
+            ..Default::default()
+        };
+        let err = run_indexed_steps(&mut keyboard, &steps).unwrap_err();
+        assert!(matches!(err, ParseKeyError::StepFailed { index: 2, .. }), "{}", err);
+        assert!(!err.is_parse_error());
+        // The failed step released Ctrl and the last step was not run
+        assert!(held_keys(&keyboard.events).is_empty());
+        assert!(!keyboard.events.contains(&(Key::Unicode('c'), Click)));
+    }
+
+    #[test]
+    fn test_held_keys_released_on_panic() {
+        let mut keyboard = RecordingKeyboard::default();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let mut held = HeldKeys::new(&mut keyboard);
+            held.press(Key::Control).unwrap();
+            held.press(Key::Shift).unwrap();
+            panic!("callback failed while keys are held");
+        }));
+        assert!(result.is_err());
+        assert_eq!(
+            keyboard.events,
+            vec![
+                (Key::Control, Press),
+                (Key::Shift, Press),
+                (Key::Shift, Release),
+                (Key::Control, Release),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_combo_released_when_pause_panics() {
+        // The pause after the modifiers panics, e.g. a custom delay source
+        let combo = parse_combination_with("ctrl+shift+t", &ParseOptions::default()).unwrap();
+        let options = SimulateOptions::new().with_pre_key_delay(Duration::from_millis(10));
+        let mut keyboard = RecordingKeyboard::default();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let mut paced = Paced::new(&mut keyboard, options).with_sleep(|_, _| panic!("interrupted"));
+            click_combo(&mut paced, &combo)
+        }));
+        assert!(result.is_err());
+        assert!(keyboard.events.contains(&(Key::Control, Press)));
+        assert!(held_keys(&keyboard.events).is_empty());
+
+        let mut keyboard = RecordingKeyboard::default();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            with_modifiers(&mut keyboard, &[Key::Control, Key::Alt], |_| panic!("interrupted"))
+        }));
+        assert!(result.is_err());
+        assert_eq!(held_keys(&keyboard.events), vec![]);
+        assert_eq!(keyboard.events.len(), 4);
+    }
+
+    #[test]
+    fn test_play_releases_held_keys_on_error() {
+        let events = [
+            (Key::Control, Press),
+            (Key::Shift, Press),
+            (Key::Shift, Release),
+            (Key::Alt, Press),
+            (Key::Tab, Click),
+            (Key::Alt, Release),
+            (Key::Control, Release),
+        ];
+        let mut keyboard = RecordingKeyboard {
+            fail_on: Some((Key::Tab, Click)),
+            ..Default::default()
+        };
+        assert!(play(&mut keyboard, &events).is_err());
+        assert_eq!(
+            keyboard.events,
+            vec![
+                (Key::Control, Press),
+                (Key::Shift, Press),
+                (Key::Shift, Release),
+                (Key::Alt, Press),
+                (Key::Alt, Release),
+                (Key::Control, Release),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_press_combination_events() {
+        let mut keyboard = RecordingKeyboard::default();
+        press_combination(&mut keyboard, "ctrl+shift+t", &ParseOptions::default()).unwrap();
+        assert_eq!(
+            keyboard.events,
+            vec![
+                (Key::Control, Press),
+                (Key::Shift, Press),
+                (Key::Unicode('t'), Click),
+                (Key::Shift, Release),
+                (Key::Control, Release),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_modifier_pressed_once() {
+        let mut keyboard = RecordingKeyboard::default();
+        press_combination(&mut keyboard, "ctrl+ctrl+c", &ParseOptions::default()).unwrap();
+        assert_eq!(
+            keyboard.events,
+            vec![
+                (Key::Control, Press),
+                (Key::Unicode('c'), Click),
+                (Key::Control, Release),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_implicit_shift_events() {
+        let options = ParseOptions {
+            implicit_shift: true,
+            ..Default::default()
+        };
+        let mut keyboard = RecordingKeyboard::default();
+        press_combination(&mut keyboard, "ctrl+T", &options).unwrap();
+        assert_eq!(
+            keyboard.events,
+            vec![
+                (Key::Control, Press),
+                (Key::Shift, Press),
+                (Key::Unicode('t'), Click),
+                (Key::Shift, Release),
+                (Key::Control, Release),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_text_combos() {
+        let shifted = |c| KeyCombo::new(vec![Key::Shift], Key::Unicode(c));
+        let plain = |key| KeyCombo::new(Vec::new(), key);
+        assert_eq!(
+            text_combos("Hi!\r\né", true).collect::<Vec<_>>(),
+            vec![
+                shifted('h'),
+                plain(Key::Unicode('i')),
+                shifted('1'),
+                plain(Key::Return),
+                plain(Key::Unicode('é')),
+            ]
+        );
+        // Letters are still shifted without the US symbol table
+        assert_eq!(
+            text_combos("H!", false).collect::<Vec<_>>(),
+            vec![shifted('h'), plain(Key::Unicode('!'))]
+        );
+    }
+
+    #[test]
+    fn test_text_as_keys_follows_parse_options() {
+        let mut simulator = recording_simulator(SimulateOptions::new(), || None);
+        simulator.text_as_keys("!").unwrap();
+        assert_eq!(recorded(&simulator), [(Key::Unicode('!'), Click)]);
+
+        let mut simulator = recording_simulator(SimulateOptions::new(), || None);
+        simulator.set_parse_options(ParseOptions {
+            us_shifted_symbols: true,
+            ..ParseOptions::default()
+        });
+        simulator.text_as_keys("!").unwrap();
+        assert_eq!(
+            recorded(&simulator),
+            [(Key::Shift, Press), (Key::Unicode('1'), Click), (Key::Shift, Release)]
+        );
+    }
+
+    #[test]
+    fn test_multiple_keys_under_held_modifiers() {
+        let mut keyboard = RecordingKeyboard::default();
+        press_combination(&mut keyboard, "alt+tab+tab", &ParseOptions::default()).unwrap();
+        assert_eq!(
+            keyboard.events,
+            vec![
+                (Key::Alt, Press),
+                (Key::Tab, Click),
+                (Key::Tab, Click),
+                (Key::Alt, Release),
+            ]
+        );
+
+        // A bad third segment is caught before Alt goes down
+        let mut keyboard = RecordingKeyboard::default();
+        assert!(press_combination(&mut keyboard, "alt+tab+notakey", &ParseOptions::default()).is_err());
+        assert!(keyboard.events.is_empty());
+
+        // A failing second click still releases Alt
+        let mut keyboard = RecordingKeyboard {
+            fail_on: Some((Key::Unicode('v'), Click)),
+            ..Default::default()
+        };
+        assert!(press_combination(&mut keyboard, "ctrl+c+v", &ParseOptions::default()).is_err());
+        assert_eq!(
+            keyboard.events,
+            vec![
+                (Key::Control, Press),
+                (Key::Unicode('c'), Click),
+                (Key::Control, Release),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_main_key_sends_nothing() {
+        let mut keyboard = RecordingKeyboard::default();
+        assert!(press_combination(&mut keyboard, "ctrl+shift+notakey", &ParseOptions::default()).is_err());
+        assert!(hold_combination(&mut keyboard, "ctrl+shift+notakey", 0, &ParseOptions::default()).is_err());
+        assert!(keyboard.events.is_empty());
+    }
+
+    #[test]
+    fn test_sequence_is_parsed_before_sending() {
+        let mut keyboard = RecordingKeyboard::default();
+        assert!(press_sequence(&mut keyboard, "a, notakey, b", &ParseOptions::default()).is_err());
+        assert!(keyboard.events.is_empty());
+    }
+
+    #[test]
+    fn test_bad_main_key_leaves_nothing_pressed() {
+        let mut keyboard = RecordingKeyboard::default();
+        assert!(press_combination(&mut keyboard, "ctrl+notakey", &ParseOptions::default()).is_err());
+        assert!(hold_combination(&mut keyboard, "alt+notakey", 0, &ParseOptions::default()).is_err());
+        assert!(press_sequence(&mut keyboard, "shift+a, ctrl+notakey", &ParseOptions::default()).is_err());
+        assert!(held_keys(&keyboard.events).is_empty());
+
+        let mut keyboard = RecordingKeyboard {
+            fail_on: Some((Key::Unicode('a'), Press)),
+            ..Default::default()
+        };
+        assert!(hold_combination(&mut keyboard, "ctrl+alt+a", 0, &ParseOptions::default()).is_err());
+        assert!(held_keys(&keyboard.events).is_empty());
+    }
+
+    #[test]
+    fn test_press_chords_releases_between_chords() {
+        let chords = parse_chord_sequence_with("ctrl+k ctrl+s", &ParseOptions::default()).unwrap();
+        let mut keyboard = RecordingKeyboard {
+            fail_on: Some((Key::Unicode('s'), Click)),
+            ..Default::default()
+        };
+        assert!(press_chords(&mut keyboard, &chords, Duration::ZERO).is_err());
+        assert_eq!(
+            keyboard.events,
+            vec![
+                (Key::Control, Press),
+                (Key::Unicode('k'), Click),
+                (Key::Control, Release),
+                (Key::Control, Press),
+                (Key::Control, Release),
+            ]
+        );
+        assert!(held_keys(&keyboard.events).is_empty());
+    }
+
+    #[test]
+    fn test_press_repeated() {
+        let combo = KeyCombo::new(vec![Key::Control], Key::Unicode('z'));
+
+        let mut keyboard = RecordingKeyboard::default();
+        press_repeated(&mut keyboard, &combo, 3, Duration::ZERO).unwrap();
+        let clicks = keyboard
+            .events
+            .iter()
+            .filter(|event| **event == (Key::Unicode('z'), Click))
+            .count();
+        assert_eq!(clicks, 3);
+        assert!(held_keys(&keyboard.events).is_empty());
+
+        let mut keyboard = RecordingKeyboard::default();
+        press_repeated(&mut keyboard, &combo, 0, Duration::from_secs(1)).unwrap();
+        assert!(keyboard.events.is_empty());
+    }
+
+    #[test]
+    fn test_multi_tap_holds_every_tap() {
+        let combo = parse_combination_with("shift", &ParseOptions::default()).unwrap();
+        let mut keyboard = RecordingKeyboard::default();
+        let mut paced = Paced::new(&mut keyboard, tap_options(SimulateOptions::default())).with_sleep(record_sleep);
+        press_repeated(&mut paced, &combo, 2, Duration::ZERO).unwrap();
+        assert_eq!(
+            keyboard.events,
+            vec![(Key::Shift, Press), (Key::Shift, Release), (Key::Shift, Press), (Key::Shift, Release)]
+        );
+        assert_eq!(keyboard.sleeps, vec![(1, MIN_TAP_HOLD), (3, MIN_TAP_HOLD)]);
+
+        // A longer hold from the options is kept
+        let long = SimulateOptions::new().with_key_hold_delay(Duration::from_millis(40));
+        assert_eq!(tap_options(long), long);
+    }
+
+    #[test]
+    fn test_press_steps() {
+        let steps = crate::parse_key_sequence("ctrl+c wait:20 ctrl+v").unwrap();
+        let mut keyboard = RecordingKeyboard::default();
+
+        let start = std::time::Instant::now();
+        press_steps(&mut keyboard, &steps, Duration::ZERO).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(20));
+        assert_eq!(
+            keyboard.events,
+            vec![
+                (Key::Control, Press),
+                (Key::Unicode('c'), Click),
+                (Key::Control, Release),
+                (Key::Control, Press),
+                (Key::Unicode('v'), Click),
+                (Key::Control, Release),
+            ]
+        );
+
+        // The gap only applies between combinations
+        let steps = crate::parse_key_sequence("a, b, c").unwrap();
+        let start = std::time::Instant::now();
+        press_steps(&mut RecordingKeyboard::default(), &steps, Duration::from_millis(10)).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_hold_all_releases_in_reverse() {
+        let keys = [Key::Unicode('a'), Key::Unicode('s'), Key::Unicode('d')];
+        let mut keyboard = RecordingKeyboard::default();
+        hold_all(&mut keyboard, &keys, Duration::ZERO).unwrap();
+        assert_eq!(
+            keyboard.events,
+            vec![
+                (Key::Unicode('a'), Press),
+                (Key::Unicode('s'), Press),
+                (Key::Unicode('d'), Press),
+                (Key::Unicode('d'), Release),
+                (Key::Unicode('s'), Release),
+                (Key::Unicode('a'), Release),
+            ]
+        );
+
+        // A failed press releases the keys that are already down
+        let mut keyboard = RecordingKeyboard {
+            fail_on: Some((Key::Unicode('d'), Press)),
+            ..Default::default()
+        };
+        assert!(hold_all(&mut keyboard, &keys, Duration::ZERO).is_err());
+        assert!(held_keys(&keyboard.events).is_empty());
+    }
+
+    #[test]
+    fn test_key_down_and_up() {
+        let combo = KeyCombo::new(vec![Key::Control, Key::Shift], Key::Unicode('a'));
+        let mut keyboard = RecordingKeyboard::default();
+
+        press_down(&mut keyboard, &combo).unwrap();
+        assert_eq!(held_keys(&keyboard.events), vec![Key::Control, Key::Shift, Key::Unicode('a')]);
+
+        release_up(&mut keyboard, &combo).unwrap();
+        assert!(held_keys(&keyboard.events).is_empty());
+        assert_eq!(
+            keyboard.events[3..],
+            [
+                (Key::Unicode('a'), Release),
+                (Key::Shift, Release),
+                (Key::Control, Release),
+            ]
+        );
+
+        // A failed press releases what already went down
+        let mut keyboard = RecordingKeyboard {
+            fail_on: Some((Key::Unicode('a'), Press)),
+            ..Default::default()
+        };
+        assert!(press_down(&mut keyboard, &combo).is_err());
+        assert!(held_keys(&keyboard.events).is_empty());
+    }
+
+    #[test]
+    fn test_press_and_release_keys() {
+        let keys = [Key::Control, Key::Shift];
+        let mut keyboard = RecordingKeyboard::default();
+        press_keys(&mut keyboard, &keys).unwrap();
+        release_keys(&mut keyboard, &keys).unwrap();
+        assert_eq!(
+            keyboard.events,
+            vec![
+                (Key::Control, Press),
+                (Key::Shift, Press),
+                (Key::Shift, Release),
+                (Key::Control, Release),
+            ]
+        );
+
+        // A failed release doesn't stop the others
+        let mut keyboard = RecordingKeyboard {
+            fail_on: Some((Key::Shift, Release)),
+            ..Default::default()
+        };
+        assert!(release_keys(&mut keyboard, &keys).is_err());
+        assert_eq!(keyboard.events, vec![(Key::Control, Release)]);
+    }
+
+    #[test]
+    fn test_backend_error_releases_pressed_modifiers() {
+        let mut keyboard = RecordingKeyboard {
+            fail_on: Some((Key::Unicode('t'), Click)),
+            ..Default::default()
+        };
+        let err = press_combination(&mut keyboard, "ctrl+shift+t", &ParseOptions::default()).unwrap_err();
+        assert!(matches!(err, ParseKeyError::Simulate(SimulateError::Backend(_))));
+        let source = std::error::Error::source(&err).unwrap();
+        assert!(source.to_string().contains("rejected by test backend"));
+        assert!(err.to_string().contains("rejected by test backend"));
+        assert_eq!(
+            keyboard.events,
+            vec![
+                (Key::Control, Press),
+                (Key::Shift, Press),
+                (Key::Shift, Release),
+                (Key::Control, Release),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_backend_errors_are_not_swallowed() {
+        let options = ParseOptions::default();
+
+        let mut keyboard = RecordingKeyboard {
+            fail_on: Some((Key::Unicode('b'), Click)),
+            ..Default::default()
+        };
+        assert!(press_sequence(&mut keyboard, "a, b, c", &options).is_err());
+        assert_eq!(keyboard.events, vec![(Key::Unicode('a'), Click)]);
+
+        // A failed release of the main key is reported, modifiers still go up
+        let mut keyboard = RecordingKeyboard {
+            fail_on: Some((Key::Space, Release)),
+            ..Default::default()
+        };
+        assert!(hold_combination(&mut keyboard, "ctrl+space", 0, &options).is_err());
+        assert_eq!(
+            keyboard.events,
+            vec![(Key::Control, Press), (Key::Space, Press), (Key::Control, Release)]
+        );
+    }
+
+    #[test]
+    fn test_failed_modifier_press_releases_earlier_modifiers() {
+        let mut keyboard = RecordingKeyboard {
+            fail_on: Some((Key::Shift, Press)),
+            ..Default::default()
+        };
+        assert!(hold_combination(&mut keyboard, "ctrl+shift+t", 0, &ParseOptions::default()).is_err());
+        assert_eq!(
+            keyboard.events,
+            vec![(Key::Control, Press), (Key::Control, Release)]
+        );
+    }
+
+    #[test]
+    fn test_paced_keyboard_waits_between_events() {
+        let mut keyboard = RecordingKeyboard::default();
+        let start = Instant::now();
+        press_combination(
+            &mut Paced::new(&mut keyboard, SimulateOptions::new().with_inter_event_delay(Duration::from_millis(10))),
+            "ctrl+shift+t",
+            &ParseOptions::default(),
+        )
+        .unwrap();
+
+        // Five events with a delay between each of them
+        assert_eq!(keyboard.events.len(), 5);
+        assert!(start.elapsed() >= Duration::from_millis(40));
+    }
+
+    #[test]
+    fn test_simulate_options_sleep_points() {
+        let ms = Duration::from_millis;
+        let options = SimulateOptions::new()
+            .with_pre_key_delay(ms(5))
+            .with_key_hold_delay(ms(7))
+            .with_inter_event_delay(ms(1));
+        let combo = parse_combination_with("ctrl+shift+t", &ParseOptions::default()).unwrap();
+
+        let mut keyboard = RecordingKeyboard::default();
+        click_combo(&mut Paced::new(&mut keyboard, options).with_sleep(record_sleep), &combo).unwrap();
+        assert_eq!(
+            keyboard.events,
+            vec![
+                (Key::Control, Press),
+                (Key::Shift, Press),
+                (Key::Unicode('t'), Press),
+                (Key::Unicode('t'), Release),
+                (Key::Shift, Release),
+                (Key::Control, Release),
+            ]
+        );
+        assert_eq!(
+            keyboard.sleeps,
+            vec![
+                // Between the modifiers
+                (1, ms(1)),
+                // Before the main key
+                (2, ms(1)),
+                (2, ms(5)),
+                // While the main key is down
+                (3, ms(7)),
+                // Between the release steps
+                (4, ms(1)),
+                (5, ms(1)),
+            ]
+        );
+
+        // Holds keep their own duration, only the pre-key delay applies
+        let mut keyboard = RecordingKeyboard::default();
+        let mut paced = Paced::new(&mut keyboard, options.with_inter_event_delay(Duration::ZERO)).with_sleep(record_sleep);
+        hold_combo(&mut paced, &combo, Duration::ZERO).unwrap();
+        assert_eq!(keyboard.sleeps, vec![(2, ms(5))]);
+        assert_eq!(keyboard.events.len(), 6);
+
+        // No options, no sleeping
+        let mut keyboard = RecordingKeyboard::default();
+        let mut paced = Paced::new(&mut keyboard, SimulateOptions::default()).with_sleep(record_sleep);
+        let steps = parse_key_sequence_with("ctrl+a wait:0 ctrl+c", &ParseOptions::default()).unwrap();
+        press_steps(&mut paced, &steps, Duration::ZERO).unwrap();
+        assert!(keyboard.sleeps.is_empty());
+        assert_eq!(keyboard.events.len(), 6);
+    }
+
+    #[test]
+    fn test_paced_keyboard_adds_random_delay() {
+        let (min, max) = (Duration::from_millis(1), Duration::from_millis(5));
+        let mut random_delay = RandomDelay::new(min, max, 1);
+        let mut expected = RandomDelay::new(min, max, 1);
+        let mut keyboard = RecordingKeyboard::default();
+        let options = SimulateOptions::new().with_inter_event_delay(Duration::from_millis(10));
+        let mut paced = Paced::new(&mut keyboard, options).with_random_delay(Some(&mut random_delay));
+
+        for _ in 0..3 {
+            assert_eq!(paced.next_delay(), Duration::from_millis(10) + expected.next_delay());
+        }
+    }
+
+    #[test]
+    fn test_has_display_server() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| vars.iter().find(|(n, _)| *n == name).map(|(_, v)| OsString::from(v))
+        };
+        assert!(!has_display_server(env(&[])));
+        assert!(!has_display_server(env(&[("DISPLAY", "")])));
+        assert!(has_display_server(env(&[("DISPLAY", ":0")])));
+        assert!(has_display_server(env(&[("WAYLAND_DISPLAY", "wayland-0")])));
+    }
+
+    #[test]
+    fn test_failed_backends_are_listed() {
+        // Neither DISPLAY nor WAYLAND_DISPLAY is set, uinput is not writable
+        let err = first_working(&[Backend::X11, Backend::Uinput], &Settings::default(), |_| None, |_, _| {
+            Err::<(), _>(SimulateError::Uinput(std::io::ErrorKind::PermissionDenied))
+        })
+        .unwrap_err();
+        assert!(err.is_backend_unavailable());
+        assert!(err.is_permission_denied());
+        let message = err.to_string();
+        assert!(message.contains(&format!("{}: ", Backend::X11.name())), "{}", message);
+        assert!(message.contains("DISPLAY is not set"), "{}", message);
+        assert!(message.contains("uinput: /dev/uinput is not writable"), "{}", message);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_backend_timeout() {
+        let err = with_deadline(Duration::from_millis(10), || {
+            std::thread::sleep(Duration::from_millis(500));
+            Ok(())
+        })
+        .unwrap_err();
+        assert_eq!(err, SimulateError::BackendTimeout(Duration::from_millis(10)));
+        assert!(err.is_backend_unavailable());
+
+        assert_eq!(with_deadline(Duration::from_secs(5), || Ok(42)), Ok(42));
+        assert!(matches!(
+            with_deadline(Duration::from_secs(5), || -> Result<(), _> { panic!("portal refused") }),
+            Err(SimulateError::Connection(_))
+        ));
+    }
+
+    #[test]
+    fn test_type_chars() {
+        let mut keyboard = RecordingKeyboard::default();
+        let start = Instant::now();
+        type_chars(&mut keyboard, "Hi!\r\nä\n", Duration::from_millis(5)).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(20));
+        assert_eq!(
+            keyboard.events,
+            vec![
+                (Key::Unicode('H'), Click),
+                (Key::Unicode('i'), Click),
+                (Key::Unicode('!'), Click),
+                (Key::Return, Click),
+                (Key::Unicode('ä'), Click),
+                (Key::Return, Click),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_type_with_modifiers() {
+        let mut keyboard = RecordingKeyboard::default();
+        type_with_modifiers(&mut keyboard, &[Key::Control, Key::Shift], "ab\n").unwrap();
+        assert_eq!(
+            keyboard.events,
+            vec![
+                (Key::Control, Press),
+                (Key::Shift, Press),
+                (Key::Unicode('a'), Click),
+                (Key::Unicode('b'), Click),
+                (Key::Return, Click),
+                (Key::Shift, Release),
+                (Key::Control, Release),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_send_bounds() {
+        fn assert_send<T: Send>() {}
+        fn assert_send_sync<T: Send + Sync + Clone>() {}
+
+        #[cfg(any(all(target_os = "linux", not(feature = "wayland")), target_os = "windows"))]
+        assert_send::<KeySimulator>();
+        assert_send_sync::<KeySimulatorFactory>();
+        assert_send::<SimulateError>();
+    }
+
+    #[test]
+    fn test_factory_copies_configuration() {
+        let mut factory = KeySimulatorFactory::default();
+        factory.set_inter_key_delay(Duration::from_millis(20));
+        factory.set_random_delay(Duration::from_millis(5), Duration::from_millis(1));
+        // Without a display only creating the backend may fail
+        if let Ok(simulator) = factory.build() {
+            assert_eq!(simulator.inter_key_delay(), Duration::from_millis(20));
+            assert_eq!(simulator.random_delay(), Some((Duration::from_millis(1), Duration::from_millis(5))));
+            assert_eq!(simulator.factory().options.inter_event_delay, Duration::from_millis(20));
+        }
+    }
+
+    #[test]
+    fn test_missing_permission() {
+        let err = connection_error(NewConError::NoPermission);
+        assert_eq!(err, SimulateError::PermissionDenied);
+        assert!(err.is_permission_denied());
+        assert!(err.to_string().contains("Privacy & Security > Accessibility"));
+    }
+}
@@ -0,0 +1,283 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use crate::CharMode;
+
+/// Pauses inserted while a key combination is sent, and how it is sent
+///
+/// Some applications miss events that arrive back to back, e.g. a main key
+/// that is pressed in the same instant as its modifiers. All delays default
+/// to zero, which sends every event right away.
+///
+/// # Examples
+/// ```no_run
+/// use std::time::Duration;
+/// use simulate_key::{simulate_key_opts, SimulateOptions};
+///
+/// let options = SimulateOptions::new()
+///     .with_pre_key_delay(Duration::from_millis(20))
+///     .with_key_hold_delay(Duration::from_millis(10));
+/// simulate_key_opts("ctrl+c", &options).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SimulateOptions {
+    /// Pause between the last modifier press and the first other key, e.g.
+    /// between Ctrl going down and C in "ctrl+c"
+    pub pre_key_delay: Duration,
+    /// How long a clicked key stays down. When zero, a single click event is
+    /// sent, otherwise a press and a release with this pause in between.
+    /// Keys that are held on purpose, e.g. with
+    /// [`simulate_key_hold`](crate::simulate_key_hold), use their own duration.
+    pub key_hold_delay: Duration,
+    /// Pause between any two consecutive key events, including the release
+    /// steps, see [`KeySimulator::set_inter_key_delay`](crate::KeySimulator::set_inter_key_delay)
+    pub inter_event_delay: Duration,
+    /// On macOS, send the modifiers of a clicked combination as `CGEventFlags`
+    /// on the key event instead of as separate key events
+    ///
+    /// Some applications only honor a shortcut when its key event carries
+    /// the flags. No Cmd or Shift event is sent at all, and the flags are
+    /// cleared once the last modifier is released. A combination of
+    /// modifiers only, e.g. "cmd" on its own, is still sent as a key event.
+    /// Only applies to clicked combinations, e.g. [`simulate_key_opts`](crate::simulate_key_opts)
+    /// or [`KeySimulator::press`](crate::KeySimulator::press), and is ignored
+    /// on other platforms.
+    pub mac_use_flags: bool,
+    /// Whether single characters, e.g. the "c" in "ctrl+c", are sent as the
+    /// character or as the key that types it, see [`CharMode`]
+    ///
+    /// Like [`mac_use_flags`](Self::mac_use_flags) it only applies to
+    /// clicked combinations.
+    pub char_mode: CharMode,
+    /// The order the modifiers of a clicked combination are released in
+    ///
+    /// Like [`mac_use_flags`](Self::mac_use_flags) it only applies to
+    /// clicked combinations, held keys are always released in reverse order.
+    pub release_order: ReleaseOrder,
+    /// Release the modifiers the user holds on the physical keyboard before
+    /// a clicked combination, and press them again afterwards
+    ///
+    /// Without it, a user still holding Shift when "ctrl+a" is sent makes
+    /// the application see Ctrl+Shift+A. Modifiers the combination uses
+    /// itself, and those the simulator holds, e.g. with
+    /// [`KeySimulator::with_modifiers`](crate::KeySimulator::with_modifiers),
+    /// are left alone. The physical state is read with
+    /// `GetAsyncKeyState` on Windows and the HID system flags on macOS,
+    /// nothing is released on Linux, which has no portable way to read it.
+    /// The state is a snapshot: a modifier the user presses or releases
+    /// while the combination is sent is not accounted for. Like
+    /// [`mac_use_flags`](Self::mac_use_flags) it only applies to clicked
+    /// combinations, and the flags sent with `mac_use_flags` already leave
+    /// out the user's modifiers.
+    pub neutralize_user_modifiers: bool,
+    /// Before sending anything, wait up to this long for the user to
+    /// release every modifier on the physical keyboard, see
+    /// [`wait_for_release`](crate::wait_for_release)
+    ///
+    /// Applies once per call of every function that sends keys, e.g. a
+    /// whole [`type_text`](crate::type_text) or
+    /// [`run_macro`](crate::run_macro), not to each key. Releasing keys never
+    /// waits, and modifiers the simulator holds itself don't count.
+    /// `SimulateError::ReleaseTimeout` is returned and nothing is sent if a
+    /// modifier is still held after the timeout. Where the keyboard cannot
+    /// be queried, [`release_fallback_delay`](Self::release_fallback_delay)
+    /// is waited instead. `None` (the default) sends right away.
+    pub wait_for_release: Option<Duration>,
+    /// The pause that replaces [`wait_for_release`](Self::wait_for_release)
+    /// where the physical keyboard cannot be queried, i.e. on Linux
+    pub release_fallback_delay: Duration,
+}
+
+/// The order the modifiers of a clicked combination are released in, see
+/// [`SimulateOptions::release_order`]
+///
+/// # Examples
+/// ```
+/// use simulate_key::{ReleaseOrder, SimulateOptions};
+///
+/// let options = SimulateOptions::new().with_release_order(ReleaseOrder::Forward);
+/// assert_eq!(options.release_order, ReleaseOrder::Forward);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ReleaseOrder {
+    /// Release the last pressed modifier first: "ctrl+shift+t" releases
+    /// Shift, then Ctrl
+    #[default]
+    Reverse,
+    /// Release the modifiers in the order they were pressed in:
+    /// "ctrl+shift+t" releases Ctrl, then Shift
+    Forward,
+}
+
+impl SimulateOptions {
+    /// Options without any delays
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set [`pre_key_delay`](Self::pre_key_delay)
+    pub fn with_pre_key_delay(mut self, delay: Duration) -> Self {
+        self.pre_key_delay = delay;
+        self
+    }
+
+    /// Set [`key_hold_delay`](Self::key_hold_delay)
+    pub fn with_key_hold_delay(mut self, delay: Duration) -> Self {
+        self.key_hold_delay = delay;
+        self
+    }
+
+    /// Set [`inter_event_delay`](Self::inter_event_delay)
+    pub fn with_inter_event_delay(mut self, delay: Duration) -> Self {
+        self.inter_event_delay = delay;
+        self
+    }
+
+    /// Set [`mac_use_flags`](Self::mac_use_flags)
+    pub fn with_mac_use_flags(mut self, mac_use_flags: bool) -> Self {
+        self.mac_use_flags = mac_use_flags;
+        self
+    }
+
+    /// Set [`char_mode`](Self::char_mode)
+    pub fn with_char_mode(mut self, char_mode: CharMode) -> Self {
+        self.char_mode = char_mode;
+        self
+    }
+
+    /// Set [`release_order`](Self::release_order)
+    pub fn with_release_order(mut self, release_order: ReleaseOrder) -> Self {
+        self.release_order = release_order;
+        self
+    }
+
+    /// Set [`neutralize_user_modifiers`](Self::neutralize_user_modifiers)
+    pub fn with_neutralize_user_modifiers(mut self, neutralize: bool) -> Self {
+        self.neutralize_user_modifiers = neutralize;
+        self
+    }
+
+    /// Set [`wait_for_release`](Self::wait_for_release)
+    pub fn with_wait_for_release(mut self, timeout: Duration) -> Self {
+        self.wait_for_release = Some(timeout);
+        self
+    }
+
+    /// Set [`release_fallback_delay`](Self::release_fallback_delay)
+    pub fn with_release_fallback_delay(mut self, delay: Duration) -> Self {
+        self.release_fallback_delay = delay;
+        self
+    }
+}
+
+/// Random delays between `min` and `max` from a small seedable generator
+///
+/// The delays only need to look irregular, so SplitMix64 is good enough and
+/// avoids a dependency on a random number crate.
+#[derive(Debug, Clone)]
+pub(crate) struct RandomDelay {
+    min: Duration,
+    max: Duration,
+    state: u64,
+}
+
+impl RandomDelay {
+    /// Delays between `min` and `max` (in either order) starting from `seed`
+    pub(crate) fn new(min: Duration, max: Duration, seed: u64) -> Self {
+        Self {
+            min: min.min(max),
+            max: min.max(max),
+            state: seed,
+        }
+    }
+
+    /// Delays between `min` and `max` with a seed that differs on every call
+    pub(crate) fn unseeded(min: Duration, max: Duration) -> Self {
+        Self::new(min, max, RandomState::new().build_hasher().finish())
+    }
+
+    /// The range delays are drawn from
+    pub(crate) fn range(&self) -> (Duration, Duration) {
+        (self.min, self.max)
+    }
+
+    /// The next delay, uniformly distributed between `min` and `max`
+    pub(crate) fn next_delay(&mut self) -> Duration {
+        let span = (self.max - self.min).as_nanos() as u64;
+        let offset = match span.checked_add(1) {
+            Some(values) => self.next_u64() % values,
+            None => self.next_u64(),
+        };
+        self.min + Duration::from_nanos(offset)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simulate_options_builder() {
+        let ms = Duration::from_millis;
+        assert_eq!(SimulateOptions::new(), SimulateOptions::default());
+        assert_eq!(
+            SimulateOptions::new()
+                .with_pre_key_delay(ms(1))
+                .with_key_hold_delay(ms(2))
+                .with_inter_event_delay(ms(3))
+                .with_mac_use_flags(true)
+                .with_char_mode(CharMode::PhysicalKey)
+                .with_release_order(ReleaseOrder::Forward)
+                .with_neutralize_user_modifiers(true)
+                .with_wait_for_release(ms(4))
+                .with_release_fallback_delay(ms(5)),
+            SimulateOptions {
+                pre_key_delay: ms(1),
+                key_hold_delay: ms(2),
+                inter_event_delay: ms(3),
+                mac_use_flags: true,
+                char_mode: CharMode::PhysicalKey,
+                release_order: ReleaseOrder::Forward,
+                neutralize_user_modifiers: true,
+                wait_for_release: Some(ms(4)),
+                release_fallback_delay: ms(5),
+            }
+        );
+    }
+
+    #[test]
+    fn test_delays_in_range() {
+        let (min, max) = (Duration::from_millis(30), Duration::from_millis(120));
+        let mut delay = RandomDelay::new(max, min, 7);
+        assert_eq!(delay.range(), (min, max));
+        let delays: Vec<Duration> = (0..1000).map(|_| delay.next_delay()).collect();
+        assert!(delays.iter().all(|d| (min..=max).contains(d)));
+        // Not constant, otherwise there is no point
+        assert!(delays.iter().any(|d| *d != delays[0]));
+
+        let mut fixed = RandomDelay::new(min, min, 7);
+        assert_eq!(fixed.next_delay(), min);
+    }
+
+    #[test]
+    fn test_seed_is_deterministic() {
+        let (min, max) = (Duration::ZERO, Duration::from_millis(100));
+        let mut a = RandomDelay::new(min, max, 42);
+        let mut b = RandomDelay::new(min, max, 42);
+        let mut c = RandomDelay::new(min, max, 43);
+        let a: Vec<Duration> = (0..10).map(|_| a.next_delay()).collect();
+        let b: Vec<Duration> = (0..10).map(|_| b.next_delay()).collect();
+        let c: Vec<Duration> = (0..10).map(|_| c.next_delay()).collect();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+}
@@ -0,0 +1,358 @@
+use std::time::Duration;
+
+use enigo::{
+    Direction::{self, Click, Press, Release},
+    InputError, InputResult, Key, Keyboard,
+};
+use evdev::uinput::VirtualDevice;
+use evdev::{AttributeSet, EventType, InputEvent, KeyCode};
+
+use crate::{
+    us_unshifted_symbol, SimulateError, XF86_AUDIO_PAUSE, XF86_MON_BRIGHTNESS_DOWN, XF86_MON_BRIGHTNESS_UP,
+    XF86_POWER_OFF, XF86_SLEEP, XF86_WAKE_UP,
+};
+
+/// Name of the virtual keyboard, e.g. in `libinput list-devices`
+const DEVICE_NAME: &str = "simulate-key virtual keyboard";
+
+/// How long a new device takes until the system reads its events
+const SETTLE_TIME: Duration = Duration::from_millis(200);
+
+/// Keyboard that sends evdev events through a virtual `/dev/uinput` device
+///
+/// This works without any display server, e.g. on the Linux console or a
+/// kiosk without X11 or Wayland, but needs write access to `/dev/uinput`.
+/// Characters are sent as the keys that type them on a US layout.
+pub(crate) struct UinputKeyboard {
+    device: VirtualDevice,
+    shift: ShiftKeys,
+}
+
+/// Which Shift keys the virtual keyboard holds down
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct ShiftKeys {
+    left: bool,
+    right: bool,
+}
+
+impl ShiftKeys {
+    /// Keep track of an event that was sent
+    fn update(&mut self, code: KeyCode, value: i32) {
+        match code {
+            KeyCode::KEY_LEFTSHIFT => self.left = value != 0,
+            KeyCode::KEY_RIGHTSHIFT => self.right = value != 0,
+            _ => {}
+        }
+    }
+
+    fn held(self) -> bool {
+        self.left || self.right
+    }
+}
+
+impl UinputKeyboard {
+    /// Create the virtual keyboard
+    ///
+    /// # Errors
+    /// Returns `SimulateError::Uinput` if `/dev/uinput` cannot be opened,
+    /// e.g. because this user may not write to it
+    pub(crate) fn new() -> Result<Self, SimulateError> {
+        // Every key up to KEY_MICMUTE plus the few named keys above it
+        let keys: AttributeSet<KeyCode> = (1..=248)
+            .map(KeyCode::new)
+            .chain([KeyCode::KEY_SELECT, KeyCode::KEY_BREAK])
+            .collect();
+        let device = VirtualDevice::builder()
+            .and_then(|builder| builder.name(DEVICE_NAME).with_keys(&keys))
+            .and_then(|builder| builder.build())
+            .map_err(|e| SimulateError::Uinput(e.kind()))?;
+
+        // Events sent before the system picked up the device are lost
+        std::thread::sleep(SETTLE_TIME);
+        Ok(Self {
+            device,
+            shift: ShiftKeys::default(),
+        })
+    }
+
+    fn emit(&mut self, code: KeyCode, value: i32) -> InputResult<()> {
+        self.device
+            .emit(&[InputEvent::new(EventType::KEY.0, code.code(), value)])
+            .map_err(|_| InputError::Simulate("failed writing to /dev/uinput"))?;
+        self.shift.update(code, value);
+        Ok(())
+    }
+}
+
+impl Keyboard for UinputKeyboard {
+    fn fast_text(&mut self, _text: &str) -> InputResult<Option<()>> {
+        // Typed key by key instead
+        Ok(None)
+    }
+
+    fn key(&mut self, key: Key, direction: Direction) -> InputResult<()> {
+        let (code, shifted) = evdev_key(key).ok_or(InputError::InvalidInput("the key has no evdev code"))?;
+        for (code, value) in key_events(code, shifted && !self.shift.held(), direction) {
+            self.emit(code, value)?;
+        }
+        Ok(())
+    }
+
+    fn raw(&mut self, keycode: u16, direction: Direction) -> InputResult<()> {
+        let code = KeyCode::new(keycode);
+        if direction != Release {
+            self.emit(code, 1)?;
+        }
+        if direction != Press {
+            self.emit(code, 0)?;
+        }
+        Ok(())
+    }
+}
+
+/// The events that send `code` in `direction`, wrapped in a press of the
+/// left Shift key if `add_shift`
+///
+/// Shift only has to be down while the key goes down, so it is released
+/// right after. Callers leave it out while a Shift key is already held, which
+/// would otherwise be released along with it.
+fn key_events(code: KeyCode, add_shift: bool, direction: Direction) -> Vec<(KeyCode, i32)> {
+    if direction == Release {
+        return vec![(code, 0)];
+    }
+    let mut events = Vec::with_capacity(4);
+    if add_shift {
+        events.push((KeyCode::KEY_LEFTSHIFT, 1));
+    }
+    events.push((code, 1));
+    if direction == Click {
+        events.push((code, 0));
+    }
+    if add_shift {
+        events.push((KeyCode::KEY_LEFTSHIFT, 0));
+    }
+    events
+}
+
+/// The evdev code of `key` and whether it needs Shift
+///
+/// Every named key of the parser has one except F25 to F35, as evdev stops
+/// at F24. Characters are mapped as on a US keyboard, so characters that are
+/// not on one, e.g. "ä", have none either.
+fn evdev_key(key: Key) -> Option<(KeyCode, bool)> {
+    let code = match key {
+        Key::Unicode(c) => return char_key(c),
+        // Modifiers, the Linux backends have no right Alt or Meta
+        Key::Control | Key::LControl => KeyCode::KEY_LEFTCTRL,
+        Key::RControl => KeyCode::KEY_RIGHTCTRL,
+        Key::Shift | Key::LShift => KeyCode::KEY_LEFTSHIFT,
+        Key::RShift => KeyCode::KEY_RIGHTSHIFT,
+        Key::Alt => KeyCode::KEY_LEFTALT,
+        Key::Meta => KeyCode::KEY_LEFTMETA,
+        // Basic keys
+        Key::Return => KeyCode::KEY_ENTER,
+        Key::Tab => KeyCode::KEY_TAB,
+        Key::Space => KeyCode::KEY_SPACE,
+        Key::Backspace => KeyCode::KEY_BACKSPACE,
+        Key::Delete => KeyCode::KEY_DELETE,
+        Key::Insert => KeyCode::KEY_INSERT,
+        Key::Escape => KeyCode::KEY_ESC,
+        // Navigation
+        Key::Home => KeyCode::KEY_HOME,
+        Key::End => KeyCode::KEY_END,
+        Key::PageUp => KeyCode::KEY_PAGEUP,
+        Key::PageDown => KeyCode::KEY_PAGEDOWN,
+        Key::LeftArrow => KeyCode::KEY_LEFT,
+        Key::RightArrow => KeyCode::KEY_RIGHT,
+        Key::UpArrow => KeyCode::KEY_UP,
+        Key::DownArrow => KeyCode::KEY_DOWN,
+        // Function keys
+        Key::F1 => KeyCode::KEY_F1,
+        Key::F2 => KeyCode::KEY_F2,
+        Key::F3 => KeyCode::KEY_F3,
+        Key::F4 => KeyCode::KEY_F4,
+        Key::F5 => KeyCode::KEY_F5,
+        Key::F6 => KeyCode::KEY_F6,
+        Key::F7 => KeyCode::KEY_F7,
+        Key::F8 => KeyCode::KEY_F8,
+        Key::F9 => KeyCode::KEY_F9,
+        Key::F10 => KeyCode::KEY_F10,
+        Key::F11 => KeyCode::KEY_F11,
+        Key::F12 => KeyCode::KEY_F12,
+        Key::F13 => KeyCode::KEY_F13,
+        Key::F14 => KeyCode::KEY_F14,
+        Key::F15 => KeyCode::KEY_F15,
+        Key::F16 => KeyCode::KEY_F16,
+        Key::F17 => KeyCode::KEY_F17,
+        Key::F18 => KeyCode::KEY_F18,
+        Key::F19 => KeyCode::KEY_F19,
+        Key::F20 => KeyCode::KEY_F20,
+        Key::F21 => KeyCode::KEY_F21,
+        Key::F22 => KeyCode::KEY_F22,
+        Key::F23 => KeyCode::KEY_F23,
+        Key::F24 => KeyCode::KEY_F24,
+        // Lock and system keys
+        Key::CapsLock => KeyCode::KEY_CAPSLOCK,
+        Key::Numlock => KeyCode::KEY_NUMLOCK,
+        Key::ScrollLock => KeyCode::KEY_SCROLLLOCK,
+        Key::PrintScr => KeyCode::KEY_SYSRQ,
+        Key::Pause => KeyCode::KEY_PAUSE,
+        Key::Help => KeyCode::KEY_HELP,
+        // The context menu key
+        Key::LMenu => KeyCode::KEY_COMPOSE,
+        Key::Other(XF86_SLEEP) => KeyCode::KEY_SLEEP,
+        Key::Other(XF86_WAKE_UP) => KeyCode::KEY_WAKEUP,
+        Key::Other(XF86_POWER_OFF) => KeyCode::KEY_POWER,
+        Key::Hangul => KeyCode::KEY_HANGEUL,
+        Key::Hanja => KeyCode::KEY_HANJA,
+        Key::Select => KeyCode::KEY_SELECT,
+        Key::MicMute => KeyCode::KEY_MICMUTE,
+        Key::Undo => KeyCode::KEY_UNDO,
+        Key::Redo => KeyCode::KEY_REDO,
+        Key::Find => KeyCode::KEY_FIND,
+        Key::SysReq => KeyCode::KEY_SYSRQ,
+        Key::Break => KeyCode::KEY_BREAK,
+        Key::Other(XF86_MON_BRIGHTNESS_UP) => KeyCode::KEY_BRIGHTNESSUP,
+        Key::Other(XF86_MON_BRIGHTNESS_DOWN) => KeyCode::KEY_BRIGHTNESSDOWN,
+        // Media keys
+        Key::VolumeUp => KeyCode::KEY_VOLUMEUP,
+        Key::VolumeDown => KeyCode::KEY_VOLUMEDOWN,
+        Key::VolumeMute => KeyCode::KEY_MUTE,
+        Key::MediaPlayPause => KeyCode::KEY_PLAYPAUSE,
+        Key::Other(XF86_AUDIO_PAUSE) => KeyCode::KEY_PAUSECD,
+        Key::MediaStop => KeyCode::KEY_STOPCD,
+        Key::MediaNextTrack => KeyCode::KEY_NEXTSONG,
+        Key::MediaPrevTrack => KeyCode::KEY_PREVIOUSSONG,
+        // Numpad
+        Key::Numpad0 => KeyCode::KEY_KP0,
+        Key::Numpad1 => KeyCode::KEY_KP1,
+        Key::Numpad2 => KeyCode::KEY_KP2,
+        Key::Numpad3 => KeyCode::KEY_KP3,
+        Key::Numpad4 => KeyCode::KEY_KP4,
+        Key::Numpad5 => KeyCode::KEY_KP5,
+        Key::Numpad6 => KeyCode::KEY_KP6,
+        Key::Numpad7 => KeyCode::KEY_KP7,
+        Key::Numpad8 => KeyCode::KEY_KP8,
+        Key::Numpad9 => KeyCode::KEY_KP9,
+        Key::Add => KeyCode::KEY_KPPLUS,
+        Key::Subtract => KeyCode::KEY_KPMINUS,
+        Key::Multiply => KeyCode::KEY_KPASTERISK,
+        Key::Divide => KeyCode::KEY_KPSLASH,
+        Key::Decimal => KeyCode::KEY_KPDOT,
+        _ => return None,
+    };
+    Some((code, false))
+}
+
+/// The key that types `c` on a US keyboard and whether it needs Shift
+fn char_key(c: char) -> Option<(KeyCode, bool)> {
+    if c.is_ascii_uppercase() {
+        return char_key(c.to_ascii_lowercase()).map(|(code, _)| (code, true));
+    }
+    if let Some(base) = us_unshifted_symbol(c) {
+        return char_key(base).map(|(code, _)| (code, true));
+    }
+
+    let code = match c {
+        'a' => KeyCode::KEY_A,
+        'b' => KeyCode::KEY_B,
+        'c' => KeyCode::KEY_C,
+        'd' => KeyCode::KEY_D,
+        'e' => KeyCode::KEY_E,
+        'f' => KeyCode::KEY_F,
+        'g' => KeyCode::KEY_G,
+        'h' => KeyCode::KEY_H,
+        'i' => KeyCode::KEY_I,
+        'j' => KeyCode::KEY_J,
+        'k' => KeyCode::KEY_K,
+        'l' => KeyCode::KEY_L,
+        'm' => KeyCode::KEY_M,
+        'n' => KeyCode::KEY_N,
+        'o' => KeyCode::KEY_O,
+        'p' => KeyCode::KEY_P,
+        'q' => KeyCode::KEY_Q,
+        'r' => KeyCode::KEY_R,
+        's' => KeyCode::KEY_S,
+        't' => KeyCode::KEY_T,
+        'u' => KeyCode::KEY_U,
+        'v' => KeyCode::KEY_V,
+        'w' => KeyCode::KEY_W,
+        'x' => KeyCode::KEY_X,
+        'y' => KeyCode::KEY_Y,
+        'z' => KeyCode::KEY_Z,
+        '1' => KeyCode::KEY_1,
+        '2' => KeyCode::KEY_2,
+        '3' => KeyCode::KEY_3,
+        '4' => KeyCode::KEY_4,
+        '5' => KeyCode::KEY_5,
+        '6' => KeyCode::KEY_6,
+        '7' => KeyCode::KEY_7,
+        '8' => KeyCode::KEY_8,
+        '9' => KeyCode::KEY_9,
+        '0' => KeyCode::KEY_0,
+        '-' => KeyCode::KEY_MINUS,
+        '=' => KeyCode::KEY_EQUAL,
+        '[' => KeyCode::KEY_LEFTBRACE,
+        ']' => KeyCode::KEY_RIGHTBRACE,
+        ';' => KeyCode::KEY_SEMICOLON,
+        '\'' => KeyCode::KEY_APOSTROPHE,
+        '`' => KeyCode::KEY_GRAVE,
+        '\\' => KeyCode::KEY_BACKSLASH,
+        ',' => KeyCode::KEY_COMMA,
+        '.' => KeyCode::KEY_DOT,
+        '/' => KeyCode::KEY_SLASH,
+        ' ' => KeyCode::KEY_SPACE,
+        '\t' => KeyCode::KEY_TAB,
+        '\n' => KeyCode::KEY_ENTER,
+        _ => return None,
+    };
+    Some((code, false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_supported_keys, parse_combination};
+
+    /// Named keys without an evdev code, see [`evdev_key`]
+    const UNSUPPORTED_KEYS: [&str; 11] = ["f25", "f26", "f27", "f28", "f29", "f30", "f31", "f32", "f33", "f34", "f35"];
+
+    #[test]
+    fn test_every_named_key_has_a_code() {
+        for name in get_supported_keys() {
+            let combo = parse_combination(name).unwrap();
+            let mapped = evdev_key(combo.key()).is_some();
+            assert_eq!(mapped, !UNSUPPORTED_KEYS.contains(&name), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_char_keys() {
+        assert_eq!(evdev_key(Key::Unicode('w')), Some((KeyCode::KEY_W, false)));
+        assert_eq!(evdev_key(Key::Unicode('W')), Some((KeyCode::KEY_W, true)));
+        assert_eq!(evdev_key(Key::Unicode('!')), Some((KeyCode::KEY_1, true)));
+        assert_eq!(evdev_key(Key::Unicode('+')), Some((KeyCode::KEY_EQUAL, true)));
+        assert_eq!(evdev_key(Key::Unicode('ä')), None);
+    }
+
+    #[test]
+    fn test_held_shift_is_kept() {
+        let shift = KeyCode::KEY_LEFTSHIFT;
+        let one = KeyCode::KEY_1;
+        assert_eq!(key_events(one, true, Click), [(shift, 1), (one, 1), (one, 0), (shift, 0)]);
+        assert_eq!(key_events(one, true, Press), [(shift, 1), (one, 1), (shift, 0)]);
+        assert_eq!(key_events(one, false, Click), [(one, 1), (one, 0)]);
+        assert_eq!(key_events(one, true, Release), [(one, 0)]);
+
+        // E.g. "shift+!", the user's Shift must stay down after the "!"
+        let mut held = ShiftKeys::default();
+        held.update(KeyCode::KEY_RIGHTSHIFT, 1);
+        assert!(held.held());
+        held.update(KeyCode::KEY_A, 1);
+        held.update(KeyCode::KEY_RIGHTSHIFT, 0);
+        assert!(!held.held());
+        held.update(shift, 1);
+        held.update(shift, 2);
+        assert!(held.held());
+    }
+}